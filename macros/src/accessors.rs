@@ -0,0 +1,85 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+
+/// `mailer: dyn Mailer` inside `typed_accessors!`
+struct Accessor {
+    name: syn::Ident,
+    service: syn::Type,
+}
+
+impl Parse for Accessor {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<syn::Token![:]>()?;
+
+        Ok(Self {
+            name,
+            service: input.parse()?,
+        })
+    }
+}
+
+/// `pub AppServices { funny: Funny, mailer: dyn Mailer }`
+pub(crate) struct Accessors {
+    vis: syn::Visibility,
+    name: syn::Ident,
+    accessors: Punctuated<Accessor, syn::Token![,]>,
+}
+
+impl Parse for Accessors {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let vis = input.parse()?;
+        let name = input.parse()?;
+        let content;
+        syn::braced!(content in input);
+
+        Ok(Self {
+            vis,
+            name,
+            accessors: content.parse_terminated(Accessor::parse, syn::Token![,])?,
+        })
+    }
+}
+
+pub(crate) fn expand(accessors: Accessors) -> syn::Result<TokenStream> {
+    let mut names: Vec<&syn::Ident> = Vec::new();
+    for accessor in &accessors.accessors {
+        if names.contains(&&accessor.name) {
+            return Err(syn::Error::new(
+                accessor.name.span(),
+                format!("`{}` is already declared as an accessor", accessor.name),
+            ));
+        }
+        names.push(&accessor.name);
+    }
+
+    let Accessors {
+        vis,
+        name,
+        accessors,
+    } = accessors;
+
+    let methods = accessors.iter().map(|a| {
+        let method = &a.name;
+        let service = &a.service;
+        let get = match service {
+            syn::Type::TraitObject(_) => quote!(get_required_dyn_service),
+            _ => quote!(get_required_service),
+        };
+        quote! {
+            fn #method(&self) -> ::deppy::Dep<#service> {
+                ::deppy::ServiceHandler::#get::<#service>(self)
+            }
+        }
+    });
+
+    Ok(quote! {
+        #vis trait #name: ::deppy::ServiceHandler + ::core::marker::Sized {
+            #(#methods)*
+        }
+
+        impl<H: ::deppy::ServiceHandler> #name for H {}
+    })
+}
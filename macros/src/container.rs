@@ -0,0 +1,124 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+
+/// `singleton Database` inside `container!`
+struct Registration {
+    lifetime: syn::Ident,
+    service: syn::Type,
+}
+
+impl Parse for Registration {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let lifetime: syn::Ident = input.parse()?;
+        if !matches!(
+            lifetime.to_string().as_str(),
+            "singleton" | "scoped" | "transient"
+        ) {
+            return Err(syn::Error::new(
+                lifetime.span(),
+                "Expected the lifetime of the service, `singleton`, `scoped` or `transient`",
+            ));
+        }
+
+        Ok(Self {
+            lifetime,
+            service: input.parse()?,
+        })
+    }
+}
+
+/// `pub struct AppContainer { singleton Database, scoped UserService }`
+pub(crate) struct Container {
+    vis: syn::Visibility,
+    name: syn::Ident,
+    registrations: Punctuated<Registration, syn::Token![,]>,
+}
+
+impl Parse for Container {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let vis = input.parse()?;
+        input.parse::<syn::Token![struct]>()?;
+        let name = input.parse()?;
+        let content;
+        syn::braced!(content in input);
+
+        Ok(Self {
+            vis,
+            name,
+            registrations: content.parse_terminated(Registration::parse, syn::Token![,])?,
+        })
+    }
+}
+
+pub(crate) fn expand(container: Container) -> syn::Result<TokenStream> {
+    // Only catches identical spellings, different spellings of a type are left to the compiler,
+    // which rejects the second `Provides` implementation for it
+    let mut registered: Vec<String> = Vec::new();
+    for registration in &container.registrations {
+        let service = &registration.service;
+        let key = quote!(#service).to_string();
+        if registered.contains(&key) {
+            return Err(syn::Error::new(
+                service.span(),
+                format!(
+                    "`{}` is already registered in this container",
+                    crate::type_to_string(service)
+                ),
+            ));
+        }
+        registered.push(key);
+    }
+
+    let Container {
+        vis,
+        name,
+        registrations,
+    } = container;
+
+    let add = registrations.iter().map(|r| {
+        let method = quote::format_ident!("add_{}", r.lifetime);
+        let service = &r.service;
+        quote! { .#method::<#service>() }
+    });
+    let provides = registrations.iter().map(|r| {
+        let service = &r.service;
+        quote! {
+            impl ::deppy::Provides<#service> for #name {
+                fn provide(&self) -> ::deppy::Dep<#service> {
+                    ::deppy::ServiceHandler::get_required_service::<#service>(&self.collection)
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #vis struct #name {
+            collection: ::deppy::ServiceCollection,
+        }
+
+        impl #name {
+            pub fn new() -> Self {
+                Self {
+                    collection: ::deppy::ServiceCollectionBuilder::default()
+                        #(#add)*
+                        .build(),
+                }
+            }
+
+            pub fn collection(&self) -> &::deppy::ServiceCollection {
+                &self.collection
+            }
+        }
+
+        impl ::core::default::Default for #name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        #(#provides)*
+    })
+}
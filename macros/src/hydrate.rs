@@ -0,0 +1,60 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::DeriveInput;
+
+fn is_injected(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(tp) => tp
+            .path
+            .segments
+            .last()
+            .is_some_and(|s| s.ident == "Injected"),
+        _ => false,
+    }
+}
+
+pub(crate) fn expand(derive: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match derive.data {
+        syn::Data::Struct(s) => s.fields,
+        _ => {
+            return Err(syn::Error::new(
+                derive.ident.span(),
+                "Can only derive on structs",
+            ))
+        }
+    };
+
+    let inject = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| is_injected(&field.ty))
+        .map(|(index, field)| {
+            let member = match &field.ident {
+                Some(ident) => quote! { #ident },
+                None => {
+                    let index = syn::Index::from(index);
+                    quote! { #index }
+                }
+            };
+            quote! { self.#member.inject(handler); }
+        })
+        .collect::<Vec<_>>();
+
+    if inject.is_empty() {
+        return Err(syn::Error::new(
+            fields.span(),
+            "Hydrate needs at least one field of type `Injected<...>`",
+        ));
+    }
+
+    let name = derive.ident;
+    let (impl_generics, ty_generics, where_clause) = derive.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::deppy::Hydrate for #name #ty_generics #where_clause {
+            fn hydrate<__H: ::deppy::ServiceHandler>(&mut self, handler: &__H) {
+                #(#inject)*
+            }
+        }
+    })
+}
@@ -1,6 +1,10 @@
 extern crate proc_macro;
 
-use darling::{FromDeriveInput, FromField};
+mod accessors;
+mod container;
+mod hydrate;
+
+use darling::{FromDeriveInput, FromField, FromMeta};
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::spanned::Spanned;
@@ -10,6 +14,82 @@ use syn::{parse_macro_input, DeriveInput};
 #[darling(attributes(injectable))]
 struct StructConfig {
     post_init: Option<syn::Path>,
+    #[darling(default)]
+    implements: TypeList,
+    /// Emits `register_singleton`, `register_scoped` and `register_transient`
+    #[darling(default)]
+    register: bool,
+    /// Declared lifetime, emits `register` next to the other `register_*` functions
+    lifetime: Option<Lifetime>,
+}
+
+/// Lifetime declared with `#[injectable(lifetime = "scoped")]`
+#[derive(FromMeta, Clone, Copy)]
+#[darling(rename_all = "snake_case")]
+enum Lifetime {
+    Singleton,
+    Scoped,
+    Transient,
+}
+
+impl Lifetime {
+    fn register_fn(self) -> syn::Ident {
+        let name = match self {
+            Lifetime::Singleton => "register_singleton",
+            Lifetime::Scoped => "register_scoped",
+            Lifetime::Transient => "register_transient",
+        };
+        syn::Ident::new(name, proc_macro2::Span::call_site())
+    }
+}
+
+/// `register_*` functions of a type deriving `Injectable`, so wiring code reads like `UserService::register(builder)`
+fn register_fns(config: &StructConfig, vis: &syn::Visibility) -> proc_macro2::TokenStream {
+    if !config.register && config.lifetime.is_none() {
+        return quote! {};
+    }
+
+    let lifetimes = [
+        (Lifetime::Singleton, quote! { add_singleton }),
+        (Lifetime::Scoped, quote! { add_scoped }),
+        (Lifetime::Transient, quote! { add_transient }),
+    ];
+    let fns = lifetimes.iter().map(|(lifetime, add)| {
+        let name = lifetime.register_fn();
+        quote! {
+            #vis fn #name(builder: ::deppy::ServiceCollectionBuilder) -> ::deppy::ServiceCollectionBuilder {
+                builder.#add::<Self>()
+            }
+        }
+    });
+    let register = config.lifetime.map(|lifetime| {
+        let name = lifetime.register_fn();
+        quote! {
+            /// Registers the service with its declared lifetime
+            #vis fn register(builder: ::deppy::ServiceCollectionBuilder) -> ::deppy::ServiceCollectionBuilder {
+                Self::#name(builder)
+            }
+        }
+    });
+
+    quote! {
+        #(#fns)*
+        #register
+    }
+}
+
+/// Comma separated types like `implements(dyn Mailer, dyn HealthCheck)`
+#[derive(Default)]
+struct TypeList(Vec<syn::Type>);
+
+impl FromMeta for TypeList {
+    fn from_meta(item: &syn::Meta) -> darling::Result<Self> {
+        let list = item.require_list()?;
+        let types = list.parse_args_with(
+            syn::punctuated::Punctuated::<syn::Type, syn::Token![,]>::parse_terminated,
+        )?;
+        Ok(Self(types.into_iter().collect()))
+    }
 }
 
 #[derive(FromField)]
@@ -17,6 +97,186 @@ struct StructConfig {
 struct FieldConfig {
     default_value: Option<syn::Lit>,
     get_value: Option<syn::Path>,
+    #[darling(default)]
+    skip: bool,
+    /// Injects the field through its `Injectable` implementation whatever its type is named,
+    /// e.g. a type alias of `Dep<T>` or `Dep` imported under another name
+    #[darling(default)]
+    dep: bool,
+    /// Converts the `Dep` in the field's type, like `Wrapper<Dep<Inner>>`, with `From`
+    #[darling(default)]
+    wrap: bool,
+    /// Converts the `Dep` in the field's type with the given function
+    wrap_with: Option<syn::Path>,
+    /// Maps a resolved dependency into the field's value with a closure annotated with the dependency,
+    /// e.g. `with = |c: Dep<Config>| c.url.clone()`
+    with: Option<syn::Expr>,
+}
+
+/// Initializer for well-known zero-sized types that don't need to come from the container
+fn zero_sized_init(ty: &syn::Type) -> Option<proc_macro2::TokenStream> {
+    match ty {
+        syn::Type::Tuple(t) if t.elems.is_empty() => Some(quote! { () }),
+        syn::Type::Path(tp) => match tp.path.segments.last()?.ident.to_string().as_str() {
+            "PhantomData" => Some(quote! { ::core::marker::PhantomData }),
+            "PhantomPinned" => Some(quote! { ::core::marker::PhantomPinned }),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn type_to_string(ty: &syn::Type) -> String {
+    quote!(#ty)
+        .to_string()
+        .replace(" :: ", "::")
+        .replace(" <", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+        .replace(" ,", ",")
+}
+
+/// Extracts `Dep<T>` out of a wrapped field of type `Wrapper<Dep<T>>`
+fn wrapped_type(field: &syn::Field) -> syn::Result<&syn::Type> {
+    let name = field
+        .ident
+        .as_ref()
+        .map(|i| i.to_string())
+        .unwrap_or_default();
+
+    let wrapped = match &field.ty {
+        syn::Type::Path(tp) => tp.path.segments.last().and_then(|s| match &s.arguments {
+            syn::PathArguments::AngleBracketed(ab) => ab.args.iter().find_map(|a| match a {
+                syn::GenericArgument::Type(t) => Some(t),
+                _ => None,
+            }),
+            _ => None,
+        }),
+        _ => None,
+    };
+
+    wrapped.ok_or_else(|| {
+        syn::Error::new(
+            field.ty.span(),
+            format!(
+                "Field `{name}` of type `{}` can't be wrapped, \
+                 its type has to wrap the dependency like `Wrapper<Dep<MyService>>`",
+                type_to_string(&field.ty)
+            ),
+        )
+    })
+}
+
+/// Extracts `T` out of a field of type `Dep<T>` or `LazyAsyncDep<T>`
+fn dependency_type<'a>(
+    field: &syn::Field,
+    ty: &'a syn::Type,
+) -> syn::Result<&'a syn::GenericArgument> {
+    let name = field
+        .ident
+        .as_ref()
+        .map(|i| i.to_string())
+        .unwrap_or_default();
+    let type_string = type_to_string(ty);
+    let not_a_dependency = |span: proc_macro2::Span| {
+        syn::Error::new(
+            span,
+            format!(
+                "Field `{name}` of type `{type_string}` can't be injected. \
+                 Wrap the service in `Dep<...>`, add `#[injectable(dep)]` if its type \
+                 is an alias of `Dep<...>`, initialize it with \
+                 `#[injectable(default_value = ...)]` or `#[injectable(get_value = ...)]`, \
+                 or add `#[injectable(skip)]` to use its `Default` value"
+            ),
+        )
+    };
+
+    let path = match ty {
+        syn::Type::Path(tp) => tp,
+        syn::Type::Reference(r) => {
+            return Err(syn::Error::new(
+                r.span(),
+                format!(
+                    "Field `{name}` is a reference, services have to be taken by value. \
+                     Use `Dep<{}>` instead",
+                    type_to_string(&r.elem)
+                ),
+            ));
+        }
+        ty => return Err(not_a_dependency(ty.span())),
+    };
+
+    let last_segment = path
+        .path
+        .segments
+        .last()
+        .ok_or_else(|| not_a_dependency(path.span()))?;
+
+    let wrapper = last_segment.ident.to_string();
+    if wrapper != "Dep" && wrapper != "LazyAsyncDep" {
+        return Err(not_a_dependency(path.span()));
+    }
+
+    let first_generic = match &last_segment.arguments {
+        syn::PathArguments::AngleBracketed(ab) => ab.args.first(),
+        _ => None,
+    };
+
+    match first_generic {
+        Some(ga @ syn::GenericArgument::Type(_)) => Ok(ga),
+        Some(ga) => Err(syn::Error::new(
+            ga.span(),
+            format!("Expected the service type of field `{name}`, e.g. `{wrapper}<MyService>`"),
+        )),
+        None => Err(syn::Error::new(
+            last_segment.span(),
+            format!(
+                "`{wrapper}` of field `{name}` is missing its service type, e.g. `{wrapper}<MyService>`"
+            ),
+        )),
+    }
+}
+
+/// Resolves the dependency of type `ty` on `service`, trait objects through `get_required_dyn_service`
+fn inject_dependency(ty: &syn::Type, service: &syn::GenericArgument) -> proc_macro2::TokenStream {
+    if matches!(
+        service,
+        syn::GenericArgument::Type(syn::Type::TraitObject(_))
+    ) {
+        quote! { ::deppy::ServiceHandler::get_required_dyn_service::<#service>(handler) }
+    } else {
+        quote! { <#ty as ::deppy::Injectable>::inject(handler) }
+    }
+}
+
+/// Parameter type and service of a `with` closure, which has to be annotated like `|c: Dep<Config>|`
+/// so the service can be declared in `dependencies`
+fn closure_dependency<'a>(
+    field: &syn::Field,
+    with: &'a syn::Expr,
+) -> syn::Result<(&'a syn::Type, &'a syn::GenericArgument)> {
+    let name = field
+        .ident
+        .as_ref()
+        .map(|i| i.to_string())
+        .unwrap_or_default();
+    let untyped = || {
+        syn::Error::new(
+            with.span(),
+            format!(
+                "`with` of field `{name}` has to be a closure annotated with the dependency it takes, \
+                 e.g. `with = |c: Dep<Config>| c.url.clone()`. Call functions from such a closure"
+            ),
+        )
+    };
+
+    let syn::Expr::Closure(closure) = with else {
+        return Err(untyped());
+    };
+    let Some(syn::Pat::Type(parameter)) = closure.inputs.first() else {
+        return Err(untyped());
+    };
+    Ok((&parameter.ty, dependency_type(field, &parameter.ty)?))
 }
 
 #[proc_macro_derive(Injectable, attributes(injectable))]
@@ -28,16 +288,47 @@ pub fn injectable(item: TokenStream) -> TokenStream {
         Err(e) => return e.write_errors().into(),
     };
 
-    let struct_ = if let syn::Data::Struct(s) = derive.data {
-        s
-    } else {
-        return syn::Error::new(derive.ident.span(), "Can only derive on structs")
+    let struct_ = match derive.data {
+        syn::Data::Struct(s) if matches!(s.fields, syn::Fields::Named(_) | syn::Fields::Unit) => s,
+        syn::Data::Struct(s) => {
+            return syn::Error::new(
+                s.fields.span(),
+                "Injectable can only be derived on structs with named fields",
+            )
             .to_compile_error()
             .into();
+        }
+        _ => {
+            return syn::Error::new(derive.ident.span(), "Can only derive on structs")
+                .to_compile_error()
+                .into();
+        }
     };
     let struct_name = derive.ident;
+    let (impl_generics, ty_generics, where_clause) = derive.generics.split_for_impl();
+    let register_fns = register_fns(&config, &derive.vis);
+    let register_fns = if register_fns.is_empty() {
+        register_fns
+    } else {
+        quote! {
+            impl #impl_generics #struct_name #ty_generics #where_clause {
+                #register_fns
+            }
+        }
+    };
 
     let mut init_fields = quote! {};
+    let mut dependencies = quote! {};
+    // Dependencies of `dep` fields, only known once their type is resolved
+    let mut extra_dependencies = quote! {};
+    let bindings = config.implements.0.iter().map(|ty| {
+        quote! { ::deppy::Binding::new::<#ty, Self>(|c| c) }
+    });
+    let bindings = quote! {
+        fn bindings() -> ::std::vec::Vec<::deppy::Binding> {
+            ::std::vec![#(#bindings),*]
+        }
+    };
 
     for field in struct_.fields {
         let field_config = match FieldConfig::from_field(&field) {
@@ -54,6 +345,91 @@ pub fn injectable(item: TokenStream) -> TokenStream {
             .into();
         }
 
+        let wrap = field_config.wrap || field_config.wrap_with.is_some();
+        if wrap
+            && (field_config.skip
+                || field_config.default_value.is_some()
+                || field_config.get_value.is_some())
+        {
+            return syn::Error::new(
+                field.span(),
+                "Cannot wrap a field that is skipped or initialized with a default value or get value",
+            )
+            .to_compile_error()
+            .into();
+        }
+
+        if field_config.dep {
+            if wrap
+                || field_config.with.is_some()
+                || field_config.skip
+                || field_config.default_value.is_some()
+                || field_config.get_value.is_some()
+            {
+                return syn::Error::new(
+                    field.span(),
+                    "Cannot combine `dep` with the other initialization attributes",
+                )
+                .to_compile_error()
+                .into();
+            }
+
+            if let Some(i) = &field.ident {
+                let ty = &field.ty;
+                init_fields = quote! {
+                    #init_fields
+                    #i: <#ty as ::deppy::Injectable>::inject(handler),
+                };
+                extra_dependencies = quote! {
+                    #extra_dependencies
+                    dependencies.extend(<#ty as ::deppy::Injectable>::dependencies());
+                };
+            }
+            continue;
+        }
+
+        if let Some(with) = &field_config.with {
+            if wrap
+                || field_config.skip
+                || field_config.default_value.is_some()
+                || field_config.get_value.is_some()
+            {
+                return syn::Error::new(
+                    field.span(),
+                    "Cannot combine `with` with the other initialization attributes",
+                )
+                .to_compile_error()
+                .into();
+            }
+
+            let (parameter, dependency) = match closure_dependency(&field, with) {
+                Ok(d) => d,
+                Err(e) => return e.to_compile_error().into(),
+            };
+            if let Some(i) = &field.ident {
+                let init = inject_dependency(parameter, dependency);
+                init_fields = quote! {
+                    #init_fields
+                    #i: (#with)(#init),
+                };
+                dependencies = quote! {
+                    #dependencies
+                    ::deppy::Dependency::of::<#dependency>(),
+                };
+            }
+            continue;
+        }
+
+        if field_config.skip {
+            if let Some(i) = field.ident {
+                init_fields = quote! {
+                    #init_fields
+                    #i: ::core::default::Default::default(),
+                }
+            }
+            continue;
+        }
+
         if let Some(gv) = field_config.get_value {
             if let Some(i) = field.ident {
                 init_fields = quote! {
@@ -74,78 +450,136 @@ pub fn injectable(item: TokenStream) -> TokenStream {
             continue;
         }
 
-        let path = match field.ty {
-            syn::Type::Path(tp) => tp,
-            _ => {
-                return syn::Error::new(field.ty.span(), "Type must be by value Dep")
-                    .to_compile_error()
-                    .into();
-            }
-        };
-
-        let last_segment = match path.path.segments.last() {
-            Some(ls) => ls,
-            None => {
-                return syn::Error::new(path.span(), "Type must be by value Dep")
-                    .to_compile_error()
-                    .into();
+        if let Some(init) = zero_sized_init(&field.ty) {
+            if let Some(i) = field.ident {
+                init_fields = quote! {
+                    #init_fields
+                    #i: #init,
+                }
             }
-        };
-
-        if last_segment.ident != "Dep" {
-            return syn::Error::new(last_segment.ident.span(), "Type must be by value Dep")
-                .to_compile_error()
-                .into();
+            continue;
         }
 
-        let angle_bracketed = match &last_segment.arguments {
-            syn::PathArguments::AngleBracketed(ab) => ab,
-            _ => {
-                return syn::Error::new(path.span(), "Type must be by value Dep")
-                    .to_compile_error()
-                    .into();
+        let ty = if wrap {
+            match wrapped_type(&field) {
+                Ok(t) => t,
+                Err(e) => return e.to_compile_error().into(),
             }
+        } else {
+            &field.ty
         };
 
-        let first_generic = match angle_bracketed.args.first() {
-            Some(ga) => ga,
-            None => {
-                return syn::Error::new(path.span(), "Type must be by value Dep")
-                    .to_compile_error()
-                    .into();
-            }
+        let first_generic = match dependency_type(&field, ty) {
+            Ok(t) => t,
+            Err(e) => return e.to_compile_error().into(),
         };
 
-        if let Some(i) = field.ident {
+        if let Some(i) = &field.ident {
+            let init = inject_dependency(ty, first_generic);
+            let init = match (&field_config.wrap_with, wrap) {
+                (Some(wrap_with), _) => quote! { #wrap_with(#init) },
+                (None, true) => quote! { ::core::convert::From::from(#init) },
+                (None, false) => init,
+            };
             init_fields = quote! {
                 #init_fields
-                #i: handler.get_required_service::<#first_generic>(),
-            }
+                #i: #init,
+            };
+            dependencies = quote! {
+                #dependencies
+                ::deppy::Dependency::of::<#first_generic>(),
+            };
         }
     }
 
+    let dependencies = if extra_dependencies.is_empty() {
+        quote! { ::std::vec![#dependencies] }
+    } else {
+        quote! {
+            let mut dependencies = ::std::vec![#dependencies];
+            #extra_dependencies
+            dependencies
+        }
+    };
+
+    let resolvable = quote! {
+        impl #impl_generics ::deppy::Resolvable for #struct_name #ty_generics #where_clause {}
+    };
+
     if let Some(pi) = config.post_init {
         quote! {
-            impl ::deppy::Injectable for #struct_name {
-                fn inject<T: ServiceHandler>(handler: &T) -> Self {
+            impl #impl_generics ::deppy::Injectable for #struct_name #ty_generics #where_clause {
+                fn inject<__H: ::deppy::ServiceHandler>(handler: &__H) -> Self {
                     let val = Self {
                        #init_fields
                     };
                     #pi(&val);
                     val
                 }
+
+                fn dependencies() -> ::std::vec::Vec<::deppy::Dependency> {
+                    #dependencies
+                }
+
+                #bindings
             }
+
+            #resolvable
+            #register_fns
         }
     } else {
         quote! {
-            impl ::deppy::Injectable for #struct_name {
-                fn inject<T: ::deppy::ServiceHandler>(handler: &T) -> Self {
+            impl #impl_generics ::deppy::Injectable for #struct_name #ty_generics #where_clause {
+                fn inject<__H: ::deppy::ServiceHandler>(handler: &__H) -> Self {
                     Self {
                        #init_fields
                     }
                 }
+
+                fn dependencies() -> ::std::vec::Vec<::deppy::Dependency> {
+                    #dependencies
+                }
+
+                #bindings
             }
+
+            #resolvable
+            #register_fns
         }
     }
     .into()
 }
+
+/// Implements `Hydrate` by resolving every `Injected` field,
+/// for structs created outside the container like deserialized jobs
+#[proc_macro_derive(Hydrate)]
+pub fn hydrate(item: TokenStream) -> TokenStream {
+    let derive = parse_macro_input!(item as DeriveInput);
+    hydrate::expand(derive)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Declares a container whose type records the registered services,
+/// e.g. `container! { pub struct AppContainer { singleton Database, scoped UserService } }`.
+/// The container implements `Provides<T>` for exactly the registered services.
+/// Registering a type twice is a compile error: the macro only sees tokens, so it reports identical spellings itself,
+/// while other spellings of the same type, e.g. a path or an alias, fail as conflicting `Provides` implementations
+#[proc_macro]
+pub fn container(item: TokenStream) -> TokenStream {
+    let container = parse_macro_input!(item as container::Container);
+    container::expand(container)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Declares an extension trait with a method per service that resolves it, implemented for every `ServiceHandler`,
+/// e.g. `typed_accessors!(pub AppServices { funny: Funny, mailer: dyn Mailer })` adds `handler.mailer()`.
+/// Trait objects are resolved with `get_required_dyn_service`, and every accessor panics like `get_required_service`
+#[proc_macro]
+pub fn typed_accessors(item: TokenStream) -> TokenStream {
+    let accessors = parse_macro_input!(item as accessors::Accessors);
+    accessors::expand(accessors)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
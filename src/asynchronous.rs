@@ -0,0 +1,558 @@
+use crate::cache::ServiceCache;
+use crate::memoize::{get_or_memoize_async, memoized_value};
+use crate::{blocking, suggest};
+use crate::{
+    CancellationToken, DefaultInitializer, Dep, Dependency, Error, Injectable, Memoize,
+    RetryPolicy, RootScopePolicy, ServiceCollection, ServiceCollectionBuilder, ServiceHandler,
+    ServiceInformation, ServiceScope, ServiceType,
+};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+pub type AsyncInitializeFn<T> = Arc<
+    dyn for<'a> Fn(&'a T) -> BoxFuture<'a, Result<Box<dyn Any + Send + Sync>, Error>> + Send + Sync,
+>;
+
+/// Owned handle resolving services asynchronously, independent of the handler it was created from
+pub type AsyncResolveFn = Arc<
+    dyn Fn(TypeId) -> BoxFuture<'static, Result<Arc<dyn Any + Send + Sync>, Error>> + Send + Sync,
+>;
+
+pub(crate) fn async_resolver<H: AsyncServiceHandler + Clone + Send + 'static>(
+    handler: &H,
+) -> AsyncResolveFn {
+    let handler = handler.clone();
+    Arc::new(move |type_id| {
+        let handler = handler.clone();
+        Box::pin(async move { handler.get_async_service_by_type_id(&type_id).await })
+    })
+}
+
+pub trait AsyncServiceHandler: ServiceHandler + Sync {
+    fn get_async_service_by_type_id<'a>(
+        &'a self,
+        type_id: &'a TypeId,
+    ) -> BoxFuture<'a, Result<Arc<dyn Any + Send + Sync>, Error>>;
+
+    fn get_async_service<T: Any + Send + Sync>(
+        &self,
+    ) -> impl Future<Output = Result<Dep<T>, Error>> + Send
+    where
+        Self: Sized,
+    {
+        self.try_get_async_service::<T>()
+    }
+
+    /// Async counterpart of `try_get_service`, reporting why the service couldn't be resolved
+    fn try_get_async_service<T: Any + Send + Sync>(
+        &self,
+    ) -> impl Future<Output = Result<Dep<T>, Error>> + Send
+    where
+        Self: Sized,
+    {
+        async move {
+            let type_id = TypeId::of::<T>();
+            let value = self
+                .get_async_service_by_type_id(&type_id)
+                .await
+                .map_err(|e| e.with_type_name(std::any::type_name::<T>()))?;
+            Ok(Dep(value.downcast::<T>().map_err(|_| {
+                Error::TypeMismatch(std::any::type_name::<T>())
+            })?))
+        }
+    }
+
+    /// Like `try_get_async_service`, but panics with the type name and the reason if it fails
+    fn get_required_async_service<T: Any + Send + Sync>(
+        &self,
+    ) -> impl Future<Output = Dep<T>> + Send
+    where
+        Self: Sized,
+    {
+        async move {
+            self.try_get_async_service::<T>()
+                .await
+                .unwrap_or_else(|error| {
+                    panic!(
+                        "{}",
+                        suggest::failed_service_message(
+                            std::any::type_name::<T>(),
+                            &error,
+                            self.registered_type_names()
+                        )
+                    )
+                })
+        }
+    }
+}
+
+/// Dependency on a service that is resolved asynchronously on the first call to `get`.
+/// Allows async services to be injected into synchronous constructors, e.g. derived `Injectable` fields.
+/// Keeps the handler it was injected from alive until it's dropped
+pub struct LazyAsyncDep<T> {
+    resolver: Option<AsyncResolveFn>,
+    value: OnceCell<Dep<T>>,
+}
+
+impl<T: Any + Send + Sync> LazyAsyncDep<T> {
+    /// Resolves the service the first time it's called and returns the same value afterwards.
+    /// Fails with `Error::NotRegistered` if it was injected by a handler that can't resolve services asynchronously
+    pub async fn get(&self) -> Result<Dep<T>, Error> {
+        self.value
+            .get_or_try_init(|| async {
+                let resolver = self.resolver.as_ref().ok_or_else(|| {
+                    Error::not_registered().with_type_name(std::any::type_name::<T>())
+                })?;
+                let value = resolver(TypeId::of::<T>()).await?;
+                Ok(Dep(value.downcast::<T>().map_err(|_| {
+                    Error::TypeMismatch(std::any::type_name::<T>())
+                })?))
+            })
+            .await
+            .cloned()
+    }
+}
+
+impl<T: Any + Send + Sync> Injectable for LazyAsyncDep<T> {
+    fn inject<H: ServiceHandler>(handler: &H) -> Self {
+        Self {
+            resolver: handler.async_resolver(),
+            value: OnceCell::new(),
+        }
+    }
+
+    fn dependencies() -> Vec<Dependency> {
+        vec![Dependency::of::<T>()]
+    }
+}
+
+pub trait AsyncInjectable: Sized {
+    fn inject<T: AsyncServiceHandler>(
+        handler: &T,
+    ) -> impl Future<Output = Result<Self, Error>> + Send;
+}
+
+/// Async counterpart of `Initialize`.
+/// Prefer `AsyncInjectable` when able to as it's less messy
+pub trait AsyncInitialize<R: Any + Send + Sync> {
+    fn initialize<T: AsyncServiceHandler>(
+        &self,
+        handler: &T,
+    ) -> impl Future<Output = Result<R, Error>> + Send;
+}
+
+impl<I: AsyncInjectable + Any + Send + Sync> AsyncInitialize<I> for DefaultInitializer {
+    fn initialize<T: AsyncServiceHandler>(
+        &self,
+        handler: &T,
+    ) -> impl Future<Output = Result<I, Error>> + Send {
+        I::inject(handler)
+    }
+}
+
+async fn run_initializer<H>(
+    information: &ServiceInformation,
+    initialize_fn: &AsyncInitializeFn<H>,
+    handler: &H,
+    cancellation_token: &CancellationToken,
+) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+    #[cfg(feature = "profiling")]
+    let started = std::time::Instant::now();
+    let value = cancellation_token
+        .run_until_cancelled(run_with_retries(information, initialize_fn, handler))
+        .await
+        .unwrap_or(Err(Error::Cancelled))
+        .map_err(|e| e.requested_by(information.type_name));
+    #[cfg(feature = "profiling")]
+    crate::profiling::record_async_initialization(information.type_name, started);
+
+    value
+}
+
+async fn run_with_retries<H>(
+    information: &ServiceInformation,
+    initialize_fn: &AsyncInitializeFn<H>,
+    handler: &H,
+) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+    let mut attempt = 1;
+    loop {
+        let result = run_attempt(information, initialize_fn, handler).await;
+        match (&result, &information.retry) {
+            (Err(e), Some(retry)) if retry.should_retry(attempt, e) => {
+                tokio::time::sleep(retry.delay(attempt)).await;
+                attempt += 1;
+            }
+            _ => return result,
+        }
+    }
+}
+
+async fn run_attempt<H>(
+    information: &ServiceInformation,
+    initialize_fn: &AsyncInitializeFn<H>,
+    handler: &H,
+) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+    let _permit = match &information.concurrency {
+        Some(limit) => limit.acquire().await,
+        None => None,
+    };
+    let future = initialize_fn(handler);
+    let value = match information.timeout {
+        Some(timeout) => tokio::time::timeout(timeout, future)
+            .await
+            .map_err(|_| Error::Timeout(information.type_name))??,
+        None => future.await?,
+    };
+
+    Ok(value.into())
+}
+
+/// Initializations currently in flight, shared so concurrent callers await the same initializer
+pub(crate) type PendingServices =
+    Arc<Mutex<HashMap<TypeId, Arc<OnceCell<Arc<dyn Any + Send + Sync>>>>>>;
+
+async fn get_or_initialize(
+    cache: &ServiceCache,
+    pending: &PendingServices,
+    type_id: &TypeId,
+    initialize: impl Future<Output = Result<Arc<dyn Any + Send + Sync>, Error>>,
+    on_created: impl FnOnce(),
+) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+    if let Some(v) = cache.get(type_id) {
+        return Ok(v);
+    }
+
+    let cell = pending
+        .lock()
+        .map_err(|_| Error::LockPoisoned)?
+        .entry(*type_id)
+        .or_default()
+        .clone();
+
+    let value = cell
+        .get_or_try_init(|| async {
+            // Another caller might have finished while this one was waiting for the pending entry
+            if let Some(v) = cache.get(type_id) {
+                return Ok(v);
+            }
+
+            let value = initialize.await?;
+            cache.insert(*type_id, value.clone());
+
+            on_created();
+            Ok(value)
+        })
+        .await?
+        .clone();
+
+    pending
+        .lock()
+        .map_err(|_| Error::LockPoisoned)?
+        .remove(type_id);
+    Ok(value)
+}
+
+/// Stands in for an async service that isn't initialized yet at the end of the middleware chain
+struct Uninitialized;
+
+/// End of the middleware chain of an async service, which runs before its initializer can be awaited.
+/// Resolves to `cached` if the service was already initialized, to a placeholder otherwise
+fn chain_end(
+    cached: Option<Arc<dyn Any + Send + Sync>>,
+) -> impl Fn() -> Result<Arc<dyn Any + Send + Sync>, Error> {
+    move || Ok(cached.clone().unwrap_or_else(|| Arc::new(Uninitialized)))
+}
+
+/// Awaits `initialize` unless the middleware chain short-circuited, substituted or vetoed the resolution
+async fn unless_resolved(
+    chained: Result<Arc<dyn Any + Send + Sync>, Error>,
+    initialize: impl Future<Output = Result<Arc<dyn Any + Send + Sync>, Error>>,
+) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+    match chained? {
+        value if value.as_ref().is::<Uninitialized>() => initialize.await,
+        value => Ok(value),
+    }
+}
+
+impl AsyncServiceHandler for ServiceCollection {
+    fn get_async_service_by_type_id<'a>(
+        &'a self,
+        type_id: &'a TypeId,
+    ) -> BoxFuture<'a, Result<Arc<dyn Any + Send + Sync>, Error>> {
+        Box::pin(async move {
+            let services = self.service_info.snapshot();
+            let information = services.get(type_id);
+            if let Some(information) = information {
+                information.check_placement(false)?;
+                match (&information.type_, self.root_scope_policy) {
+                    (ServiceType::Scoped, RootScopePolicy::ImplicitScope) => {
+                        return self
+                            .root_scope()
+                            .get_async_service_by_type_id(type_id)
+                            .await;
+                    }
+                    (ServiceType::Scoped, RootScopePolicy::Error) => {
+                        return Err(Error::ScopedFromRoot(information.type_name));
+                    }
+                    _ => {}
+                }
+            }
+
+            let Some((information, initialize_fn)) =
+                information.and_then(|i| Some((i, i.initialize_async_fn.as_ref()?)))
+            else {
+                if information.is_some_and(|i| i.blocking) && self.singletons.get(type_id).is_none()
+                {
+                    return blocking::resolve(self, type_id).await;
+                }
+                return self.try_get_service_by_type_id(type_id);
+            };
+
+            let cached = match (&information.type_, &information.memoize) {
+                (ServiceType::Singleton, _) => self.singletons.get(type_id),
+                (_, Some(Memoize::Ttl(ttl))) => memoized_value(&self.memoized, type_id, *ttl),
+                _ => None,
+            };
+            let chained = self.resolve_through(type_id, Some(information), &chain_end(cached));
+
+            let initialize =
+                run_initializer(information, initialize_fn, self, &self.cancellation_token);
+            unless_resolved(chained, async {
+                match (&information.type_, &information.memoize) {
+                    (ServiceType::Singleton, _) => {
+                        get_or_initialize(
+                            &self.singletons,
+                            &self.pending_singletons,
+                            type_id,
+                            initialize,
+                            || {
+                                for observer in self.observers.iter() {
+                                    observer.on_singleton_created(type_id);
+                                }
+                            },
+                        )
+                        .await
+                    }
+                    (_, Some(Memoize::Ttl(ttl))) => {
+                        get_or_memoize_async(&self.memoized, type_id, *ttl, initialize).await
+                    }
+                    _ => initialize.await,
+                }
+            })
+            .await
+        })
+    }
+}
+
+impl ServiceCollection {
+    /// Awaits the initializers of every async singleton in registration order and caches the results.
+    /// Afterwards the singletons can also be resolved synchronously,
+    /// e.g. as `Dep` fields of services deriving `Injectable`
+    pub async fn initialize_async_singletons(&self) -> Result<(), Error> {
+        let services = self.service_info.snapshot();
+        let type_ids = services
+            .iter()
+            .filter(|(_, i)| {
+                matches!(i.type_, ServiceType::Singleton) && i.initialize_async_fn.is_some()
+            })
+            .map(|(type_id, _)| type_id);
+
+        for type_id in type_ids {
+            self.get_async_service_by_type_id(type_id).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl AsyncServiceHandler for ServiceScope {
+    fn get_async_service_by_type_id<'a>(
+        &'a self,
+        type_id: &'a TypeId,
+    ) -> BoxFuture<'a, Result<Arc<dyn Any + Send + Sync>, Error>> {
+        Box::pin(async move {
+            if self.overrides.contains_key(type_id) {
+                return self.try_get_service_by_type_id(type_id);
+            }
+            let services = self.services.snapshot();
+            let information = services.get(type_id);
+            if let Some(information) = information {
+                information.check_placement(true)?;
+            }
+            let Some((information, initialize_fn)) =
+                information.and_then(|i| Some((i, i.scoped_initialize_async_fn.as_ref()?)))
+            else {
+                let cached =
+                    self.singletons.get(type_id).is_some() || self.scoped.get(type_id).is_some();
+                if information.is_some_and(|i| i.blocking) && !cached {
+                    return blocking::resolve(self, type_id).await;
+                }
+                return self.try_get_service_by_type_id(type_id);
+            };
+
+            let cached = match (&information.type_, &information.memoize) {
+                (ServiceType::Singleton, _) => self.singletons.get(type_id),
+                (ServiceType::Scoped, _) | (ServiceType::Transient, Some(Memoize::PerScope)) => {
+                    self.scoped.get(type_id)
+                }
+                (ServiceType::Transient, Some(Memoize::Ttl(ttl))) => {
+                    memoized_value(&self.memoized, type_id, *ttl)
+                }
+                (ServiceType::Transient, None) => None,
+            };
+            let chained = self.resolve_through(type_id, Some(information), &chain_end(cached));
+
+            let observers = &self.lifecycle.observers;
+            let initialize =
+                run_initializer(information, initialize_fn, self, &self.cancellation_token);
+            // Services shared with other scopes must not see the overrides of this one
+            let handler = self.shared_handler();
+            unless_resolved(chained, async {
+                match (&information.type_, &information.memoize) {
+                    (ServiceType::Singleton, _) => {
+                        get_or_initialize(
+                            &self.singletons,
+                            &self.pending_singletons,
+                            type_id,
+                            run_initializer(
+                                information,
+                                initialize_fn,
+                                &handler,
+                                &self.cancellation_token,
+                            ),
+                            || {
+                                for observer in observers.iter() {
+                                    observer.on_singleton_created(type_id);
+                                }
+                            },
+                        )
+                        .await
+                    }
+                    (ServiceType::Scoped, _)
+                    | (ServiceType::Transient, Some(Memoize::PerScope)) => {
+                        get_or_initialize(
+                            &self.scoped,
+                            &self.pending_scoped,
+                            type_id,
+                            initialize,
+                            || {},
+                        )
+                        .await
+                    }
+                    (ServiceType::Transient, Some(Memoize::Ttl(ttl))) => {
+                        let initialize = run_initializer(
+                            information,
+                            initialize_fn,
+                            &handler,
+                            &self.cancellation_token,
+                        );
+                        get_or_memoize_async(&self.memoized, type_id, *ttl, initialize).await
+                    }
+                    (ServiceType::Transient, None) => initialize.await,
+                }
+            })
+            .await
+        })
+    }
+}
+
+impl ServiceCollectionBuilder {
+    /// Registers a service that is initialized asynchronously.
+    /// Async services can only be resolved through `AsyncServiceHandler`
+    pub fn add_async_service<
+        T: Any + Send + Sync,
+        I: AsyncInitialize<T> + Clone + Send + Sync + 'static,
+    >(
+        mut self,
+        type_: ServiceType,
+        initializer: I,
+    ) -> Self {
+        let closure_clone = initializer.clone();
+        let collection_closure: AsyncInitializeFn<ServiceCollection> = Arc::new(move |x| {
+            let initializer = closure_clone.clone();
+            Box::pin(async move {
+                Ok(Box::new(initializer.initialize(x).await?) as Box<dyn Any + Send + Sync>)
+            })
+        });
+        let scoped_closure: AsyncInitializeFn<ServiceScope> = Arc::new(move |x| {
+            let initializer = initializer.clone();
+            Box::pin(async move {
+                Ok(Box::new(initializer.initialize(x).await?) as Box<dyn Any + Send + Sync>)
+            })
+        });
+
+        let information = ServiceInformation {
+            initialize_fn: None,
+            scoped_initialize_fn: None,
+            initialize_async_fn: Some(collection_closure),
+            scoped_initialize_async_fn: Some(scoped_closure),
+            type_,
+            type_name: std::any::type_name::<T>(),
+            memoize: None,
+            timeout: None,
+            retry: None,
+            data: Default::default(),
+            receives_data: false,
+            dependencies: Vec::new(),
+            implementation: None,
+            deprecated: None,
+            memory_footprint: None,
+            async_dispose: None,
+            placement: Default::default(),
+            concurrency: None,
+            blocking: false,
+            stable_key: None,
+        };
+
+        self.register(TypeId::of::<T>(), information);
+        self.last_registered = Some(TypeId::of::<T>());
+
+        self
+    }
+
+    pub fn add_async_singleton<T: AsyncInjectable + Any + Send + Sync>(self) -> Self {
+        self.add_async_service::<T, DefaultInitializer>(ServiceType::Singleton, DefaultInitializer)
+    }
+
+    pub fn add_async_scoped<T: AsyncInjectable + Any + Send + Sync>(self) -> Self {
+        self.add_async_service::<T, DefaultInitializer>(ServiceType::Scoped, DefaultInitializer)
+    }
+
+    pub fn add_async_transient<T: AsyncInjectable + Any + Send + Sync>(self) -> Self {
+        self.add_async_service::<T, DefaultInitializer>(ServiceType::Transient, DefaultInitializer)
+    }
+
+    /// Sets how long the async initializer of the last registered service may run
+    /// before resolution fails with `Error::Timeout`.
+    /// Requires a tokio runtime with the time driver enabled
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.last_registration().timeout = Some(timeout);
+        self
+    }
+
+    /// Retries the async initializer of the last registered service according to the policy.
+    /// The timeout applies to every attempt separately
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.last_registration().retry = Some(policy);
+        self
+    }
+
+    /// Uses the given token instead of a fresh one as the container's cancellation token
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Sets the timeout for every async initializer without an explicit timeout
+    pub fn default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+}
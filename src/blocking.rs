@@ -0,0 +1,33 @@
+use crate::{Error, ServiceCollectionBuilder, ServiceHandler};
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+impl ServiceCollectionBuilder {
+    /// Runs the synchronous initializer of the last registered service on tokio's blocking thread pool
+    /// when it's resolved through `AsyncServiceHandler`, so a slow constructor doesn't stall the runtime.
+    /// Synchronous resolution still runs it on the calling thread
+    pub fn blocking(mut self) -> Self {
+        self.last_registration().blocking = true;
+        self
+    }
+}
+
+/// Resolves a service registered with `blocking` on the blocking thread pool.
+/// A panicking initializer panics the caller like it does when resolving synchronously
+pub(crate) async fn resolve<H>(
+    handler: &H,
+    type_id: &TypeId,
+) -> Result<Arc<dyn Any + Send + Sync>, Error>
+where
+    H: ServiceHandler + Clone + Send + 'static,
+{
+    let handler = handler.clone();
+    let type_id = *type_id;
+    match tokio::task::spawn_blocking(move || handler.try_get_service_by_type_id(&type_id)).await {
+        Ok(value) => value,
+        Err(error) => match error.try_into_panic() {
+            Ok(panic) => std::panic::resume_unwind(panic),
+            Err(_) => Err(Error::Cancelled),
+        },
+    }
+}
@@ -0,0 +1,98 @@
+use crate::placement::Placement;
+use crate::validation::{find_problems, validate_dependencies, Checks};
+use crate::{Error, ServiceCollection, ServiceCollectionBuilder, ServiceHandler, ServiceType};
+
+/// Checks and work done by `build_with` before the collection is handed out.
+/// Everything is off by default, which matches `build` apart from sections bound with `bind_section`,
+/// which `build_with` always deserializes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BuildOptions {
+    /// Fails if a declared dependency of any service isn't registered or the dependencies form a cycle
+    pub validate_graph: bool,
+    /// Fails if a singleton depends on a scoped service, directly or through transient services
+    pub validate_scopes: bool,
+    /// Initializes every singleton with a synchronous initializer in registration order.
+    /// Async singletons are left to `initialize_async_singletons`
+    pub eager_singletons: bool,
+    /// Fails if a service was registered more than once instead of keeping the last registration
+    pub fail_on_duplicates: bool,
+}
+
+impl ServiceCollectionBuilder {
+    /// Builds the collection like `build`, applying the checks enabled in `options`.
+    /// Only dependencies declared through `Injectable::dependencies` can be validated
+    pub fn build_with(mut self, options: BuildOptions) -> Result<ServiceCollection, Error> {
+        if let Some(error) = self.select_env().into_iter().next() {
+            return Err(error);
+        }
+        if options.fail_on_duplicates {
+            if let Some(type_name) = self.duplicates.first() {
+                return Err(Error::DuplicateRegistration(type_name));
+            }
+        }
+
+        #[cfg(feature = "figment")]
+        let sections = self.sections.clone();
+        let collection = self.build();
+        let services = collection.service_info.snapshot();
+
+        #[cfg(feature = "figment")]
+        crate::figment::extract_sections(&collection, &sections)?;
+
+        if options.validate_graph || options.validate_scopes {
+            let checks = Checks {
+                graph: options.validate_graph,
+                lifetimes: options.validate_scopes,
+            };
+            validate_dependencies(&services, services.iter().map(|(t, _)| t), checks)?;
+        }
+
+        if options.eager_singletons {
+            let singletons = services.iter().filter(|(_, information)| {
+                matches!(information.type_, ServiceType::Singleton)
+                    && information.initialize_fn.is_some()
+                    && information.placement != Placement::ScopeOnly
+            });
+            for (type_id, _) in singletons {
+                collection.try_get_service_by_type_id(type_id)?;
+            }
+        }
+
+        Ok(collection)
+    }
+
+    /// Builds the collection after checking every registration without resolving anything,
+    /// returning all problems found instead of the first one: dependencies that aren't registered,
+    /// singletons depending on scoped services, dependency cycles and, with `bind_section`, invalid configuration.
+    /// Only dependencies declared through `Injectable::dependencies` can be checked
+    pub fn try_build(mut self) -> Result<ServiceCollection, Vec<Error>> {
+        let environment = self.select_env();
+        if !environment.is_empty() {
+            return Err(environment);
+        }
+
+        #[cfg(feature = "figment")]
+        let sections = self.sections.clone();
+        let collection = self.build();
+        #[cfg(feature = "figment")]
+        let configuration = crate::figment::extract_sections(&collection, &sections).err();
+        #[cfg(not(feature = "figment"))]
+        let configuration = None;
+
+        let services = collection.service_info.snapshot();
+        let checks = Checks {
+            graph: true,
+            lifetimes: true,
+        };
+        let problems: Vec<_> = find_problems(&services, services.iter().map(|(t, _)| t), checks)
+            .into_iter()
+            .chain(configuration)
+            .collect();
+
+        if problems.is_empty() {
+            Ok(collection)
+        } else {
+            Err(problems)
+        }
+    }
+}
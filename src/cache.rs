@@ -0,0 +1,115 @@
+use crate::type_map::TypeMap;
+use crate::{Registry, ServiceCollection, ServiceScope};
+use arc_swap::ArcSwap;
+use std::any::{Any, TypeId};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
+
+/// Initialized services of a lifetime.
+/// Lookups are a wait-free load of an immutable map, only inserting takes a lock
+/// and swaps in an extended copy, which happens once per service
+#[derive(Default)]
+pub(crate) struct ServiceCache {
+    services: ArcSwap<TypeMap<Arc<dyn Any + Send + Sync>>>,
+    /// When each value was inserted, locked while writing
+    inserted: Mutex<TypeMap<Instant>>,
+}
+
+impl ServiceCache {
+    pub(crate) fn get(&self, type_id: &TypeId) -> Option<Arc<dyn Any + Send + Sync>> {
+        self.services.load().get(type_id).cloned()
+    }
+
+    pub(crate) fn insert(&self, type_id: TypeId, value: Arc<dyn Any + Send + Sync>) {
+        let mut inserted = self.inserted.lock().unwrap_or_else(PoisonError::into_inner);
+        inserted.insert(type_id, Instant::now());
+        let mut services = TypeMap::clone(&self.services.load());
+        services.insert(type_id, value);
+        self.services.store(Arc::new(services));
+    }
+
+    pub(crate) fn snapshot(&self) -> Arc<TypeMap<Arc<dyn Any + Send + Sync>>> {
+        self.services.load_full()
+    }
+
+    /// Every value with the time it was inserted
+    pub(crate) fn entries(&self) -> Vec<(Instant, TypeId, Arc<dyn Any + Send + Sync>)> {
+        let inserted = self.inserted.lock().unwrap_or_else(PoisonError::into_inner);
+        with_times(&self.services.load(), &inserted)
+    }
+
+    /// Removes every value, returning them with the time they were inserted
+    pub(crate) fn take(&self) -> Vec<(Instant, TypeId, Arc<dyn Any + Send + Sync>)> {
+        let mut inserted = self.inserted.lock().unwrap_or_else(PoisonError::into_inner);
+        let inserted = std::mem::take(&mut *inserted);
+        with_times(&self.services.swap(Default::default()), &inserted)
+    }
+
+    /// Returns whether a value was removed
+    pub(crate) fn remove(&self, type_id: &TypeId) -> bool {
+        let mut inserted = self.inserted.lock().unwrap_or_else(PoisonError::into_inner);
+        let current = self.services.load();
+        if !current.contains_key(type_id) {
+            return false;
+        }
+
+        let services = current
+            .iter()
+            .filter(|(id, _)| *id != type_id)
+            .map(|(id, value)| (*id, value.clone()))
+            .collect();
+        self.services.store(Arc::new(services));
+        *inserted = inserted
+            .iter()
+            .filter(|(id, _)| *id != type_id)
+            .map(|(id, time)| (*id, *time))
+            .collect();
+        true
+    }
+
+    /// Pairs every initialized service with its registered type name
+    fn named(&self, registry: &Registry) -> Vec<(&'static str, Arc<dyn Any + Send + Sync>)> {
+        let information = registry.snapshot();
+        self.services
+            .load()
+            .iter()
+            .filter_map(|(type_id, value)| {
+                Some((information.get(type_id)?.type_name, value.clone()))
+            })
+            .collect()
+    }
+}
+
+fn with_times(
+    services: &TypeMap<Arc<dyn Any + Send + Sync>>,
+    inserted: &TypeMap<Instant>,
+) -> Vec<(Instant, TypeId, Arc<dyn Any + Send + Sync>)> {
+    services
+        .iter()
+        .filter_map(|(type_id, value)| Some((*inserted.get(type_id)?, *type_id, value.clone())))
+        .collect()
+}
+
+impl ServiceCollection {
+    /// Singletons that have been initialized so far, in the order they were initialized
+    pub fn singletons_iter(
+        &self,
+    ) -> impl Iterator<Item = (&'static str, Arc<dyn Any + Send + Sync>)> {
+        self.singletons.named(&self.service_info).into_iter()
+    }
+}
+
+impl ServiceScope {
+    /// Singletons that have been initialized so far, in the order they were initialized.
+    /// Singletons are shared with the root collection and its other scopes
+    pub fn singletons_iter(
+        &self,
+    ) -> impl Iterator<Item = (&'static str, Arc<dyn Any + Send + Sync>)> {
+        self.singletons.named(&self.services).into_iter()
+    }
+
+    /// Scoped services this scope has initialized so far, in the order they were initialized
+    pub fn scoped_iter(&self) -> impl Iterator<Item = (&'static str, Arc<dyn Any + Send + Sync>)> {
+        self.scoped.named(&self.services).into_iter()
+    }
+}
@@ -0,0 +1,38 @@
+use crate::trait_object::TraitObject;
+use crate::BoxFuture;
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Source of time for services, resolvable as `Dep<dyn Clock>`.
+/// Resolves to `SystemClock` unless another implementation is registered,
+/// tests can register `testing::TestClock` to control time
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'_, ()>;
+}
+
+/// `Clock` backed by the system's monotonic clock and tokio's timer
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'_, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// `SystemClock` as a `dyn Clock` service if `type_id` is an unregistered `dyn Clock`
+pub(crate) fn default_service(type_id: &TypeId) -> Option<Arc<dyn Any + Send + Sync>> {
+    if *type_id != TypeId::of::<dyn Clock>() {
+        return None;
+    }
+
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+    Some(Arc::new(TraitObject::from_object(clock)))
+}
@@ -0,0 +1,29 @@
+use crate::ServiceCollectionBuilder;
+use std::sync::Arc;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Group of async registrations whose initializers share a number of permits,
+/// e.g. so at most 2 expensive warm-ups run at once during startup
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit(Arc<Semaphore>);
+
+impl ConcurrencyLimit {
+    pub fn new(permits: usize) -> Self {
+        Self(Arc::new(Semaphore::new(permits)))
+    }
+
+    pub(crate) async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+        // The semaphore is never closed
+        self.0.acquire().await.ok()
+    }
+}
+
+impl ServiceCollectionBuilder {
+    /// Adds the async initializer of the last registered service to `limit`.
+    /// Every attempt waits for a permit before its timeout starts. An initializer shouldn't resolve
+    /// another service of the same group, as it holds a permit while waiting for it
+    pub fn concurrency_limit(mut self, limit: &ConcurrencyLimit) -> Self {
+        self.last_registration().concurrency = Some(limit.clone());
+        self
+    }
+}
@@ -0,0 +1,152 @@
+use crate::type_map::TypeMap;
+use crate::{Error, Memoize, ServiceCollection, ServiceInformation, ServiceScope, ServiceType};
+use std::any::TypeId;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// A registration being initialized: the container or scope that stores or creates the value,
+/// the type, and the index of keyed and replaced registrations
+#[derive(PartialEq, Eq, Clone, Copy)]
+struct Initialization {
+    owner: usize,
+    type_id: TypeId,
+    index: Option<usize>,
+}
+
+thread_local! {
+    /// Registrations being initialized on this thread, outermost first, with their type names
+    static INITIALIZING: RefCell<Vec<(Initialization, &'static str)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Container or scope an initializer runs in
+pub(crate) trait Initializing {
+    /// Address of the cache the value of `information` ends up in,
+    /// or of the container or scope creating it if it isn't cached
+    fn owner(&self, information: &ServiceInformation) -> usize;
+
+    /// The cycle the declared dependencies of `type_id` lead into, if any
+    fn declared_cycle(&self, type_id: &TypeId) -> Option<Vec<&'static str>>;
+}
+
+impl Initializing for ServiceCollection {
+    fn owner(&self, _information: &ServiceInformation) -> usize {
+        Arc::as_ptr(&self.singletons) as usize
+    }
+
+    fn declared_cycle(&self, type_id: &TypeId) -> Option<Vec<&'static str>> {
+        self.service_info.declared_cycle(type_id)
+    }
+}
+
+impl Initializing for ServiceScope {
+    fn owner(&self, information: &ServiceInformation) -> usize {
+        match (&information.type_, &information.memoize) {
+            (ServiceType::Singleton, _) | (ServiceType::Transient, Some(Memoize::Ttl(_))) => {
+                Arc::as_ptr(&self.singletons) as usize
+            }
+            _ => Arc::as_ptr(&self.scoped) as usize,
+        }
+    }
+
+    fn declared_cycle(&self, type_id: &TypeId) -> Option<Vec<&'static str>> {
+        self.services.declared_cycle(type_id)
+    }
+}
+
+/// Marks a registration as being initialized on this thread until it's dropped
+pub(crate) struct InitializingGuard(());
+
+impl Drop for InitializingGuard {
+    fn drop(&mut self) {
+        INITIALIZING.with_borrow_mut(|stack| stack.pop());
+    }
+}
+
+/// Fails with `Error::Cycle` before the initializer runs if the declared dependencies of the registration
+/// lead into a cycle, e.g. derived services `A` and `B` injecting each other.
+/// Otherwise fails if the initializer is already running further up the stack for the same container or scope,
+/// e.g. because the factory of `A` resolves `B` which resolves `A`, instead of recursing until the stack overflows.
+/// `index` is the index of keyed and replaced registrations. Async initializers aren't tracked,
+/// the thread can change at every await
+pub(crate) fn enter(
+    information: &ServiceInformation,
+    handler: &impl Initializing,
+    type_id: TypeId,
+    index: Option<usize>,
+) -> Result<InitializingGuard, Error> {
+    if index.is_none() {
+        if let Some(path) = handler.declared_cycle(&type_id) {
+            return Err(Error::Cycle { path });
+        }
+    }
+
+    let initialization = Initialization {
+        owner: handler.owner(information),
+        type_id,
+        index,
+    };
+    INITIALIZING.with_borrow_mut(|stack| {
+        if let Some(start) = stack.iter().position(|(i, _)| *i == initialization) {
+            let path = stack[start..]
+                .iter()
+                .map(|(_, type_name)| *type_name)
+                .chain([information.type_name])
+                .collect();
+            return Err(Error::Cycle { path });
+        }
+
+        stack.push((initialization, information.type_name));
+        Ok(InitializingGuard(()))
+    })
+}
+
+/// Every service whose declared dependencies lead into a cycle, with the cycle.
+/// Services that can only be initialized asynchronously end a path, as resolving them synchronously fails anyway
+/// and `LazyAsyncDep` declares them without resolving them during injection
+pub(crate) fn declared_cycles(
+    services: &TypeMap<ServiceInformation>,
+) -> TypeMap<Vec<&'static str>> {
+    let mut cycles = TypeMap::default();
+    let mut done = HashSet::new();
+    for (type_id, _) in services.iter() {
+        find_cycle(services, type_id, &mut Vec::new(), &mut done, &mut cycles);
+    }
+    cycles
+}
+
+fn find_cycle(
+    services: &TypeMap<ServiceInformation>,
+    root: &TypeId,
+    path: &mut Vec<TypeId>,
+    done: &mut HashSet<TypeId>,
+    cycles: &mut TypeMap<Vec<&'static str>>,
+) -> Option<Vec<&'static str>> {
+    if let Some(start) = path.iter().position(|t| t == root) {
+        let cycle = path[start..]
+            .iter()
+            .chain([root])
+            .filter_map(|t| Some(services.get(t)?.type_name))
+            .collect();
+        return Some(cycle);
+    }
+    if done.contains(root) {
+        return cycles.get(root).cloned();
+    }
+    let information = services
+        .get(root)
+        .filter(|information| information.initialize_fn.is_some())?;
+
+    path.push(*root);
+    let cycle = information
+        .dependencies
+        .iter()
+        .find_map(|dependency| find_cycle(services, &dependency.type_id, path, done, cycles));
+    path.pop();
+
+    done.insert(*root);
+    if let Some(cycle) = &cycle {
+        cycles.insert(*root, cycle.clone());
+    }
+    cycle
+}
@@ -0,0 +1,31 @@
+use crate::ServiceCollectionBuilder;
+use std::any::Any;
+use std::sync::Arc;
+
+/// Payload attached to a registration through `with_data`,
+/// handed to `Initialize::initialize_with_data` so one initializer can serve differently configured registrations
+#[derive(Clone, Default)]
+pub struct RegistrationData(Option<Arc<dyn Any + Send + Sync>>);
+
+impl RegistrationData {
+    /// Returns the payload if one is attached and it is of type `D`
+    pub fn get<D: Any>(&self) -> Option<&D> {
+        self.0.as_ref()?.downcast_ref()
+    }
+}
+
+impl ServiceCollectionBuilder {
+    /// Attaches a payload to the last registered service that is passed to its `Initialize` implementation.
+    /// Replaces any payload attached before.
+    /// Panics if the service is created by a closure or an async initializer, which can't take the payload
+    pub fn with_data<D: Any + Send + Sync>(mut self, data: D) -> Self {
+        let registration = self.last_registration();
+        assert!(
+            registration.receives_data,
+            "Service {} can't take data, only `Initialize` implementations are handed the payload of `with_data`",
+            registration.type_name
+        );
+        registration.data = RegistrationData(Some(Arc::new(data)));
+        self
+    }
+}
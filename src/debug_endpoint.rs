@@ -0,0 +1,218 @@
+//! Framework-agnostic debug endpoint describing the state of a container as JSON or HTML
+
+use crate::{Observer, RegistrationSummary, ServiceCollection, ServiceCollectionBuilder};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Number of resolution errors kept for the endpoint
+const RECENT_ERRORS: usize = 32;
+
+#[derive(Default)]
+struct DebugState {
+    live_scopes: AtomicUsize,
+    /// `(service, error)`, oldest first
+    errors: Mutex<VecDeque<(&'static str, String)>>,
+}
+
+impl Observer for DebugState {
+    fn on_scope_created(&self) {
+        self.live_scopes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_scope_dropped(&self) {
+        self.live_scopes.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Body of a response produced by `DebugEndpoint::respond`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugResponse {
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+/// Describes registrations in registration order, initialized singletons, live scopes and recent resolution errors.
+/// Has to be installed on the builder through `debug_endpoint` to track scopes and errors,
+/// serving the response is left to the web framework
+#[derive(Clone, Default)]
+pub struct DebugEndpoint {
+    state: Arc<DebugState>,
+}
+
+impl DebugEndpoint {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Responds with HTML if `accept` contains `text/html`, and with JSON otherwise
+    pub fn respond(&self, collection: &ServiceCollection, accept: Option<&str>) -> DebugResponse {
+        if accept.is_some_and(|accept| accept.contains("text/html")) {
+            DebugResponse {
+                content_type: "text/html; charset=utf-8",
+                body: self.html(collection),
+            }
+        } else {
+            DebugResponse {
+                content_type: "application/json",
+                body: self.json(collection),
+            }
+        }
+    }
+
+    pub fn json(&self, collection: &ServiceCollection) -> String {
+        let report = Report::new(self, collection);
+        let strings = |values: &[&str]| {
+            values
+                .iter()
+                .map(|value| format!("\"{}\"", escape_json(value)))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        let registrations = report
+            .registrations
+            .iter()
+            .map(|r| {
+                format!(
+                    "{{\"type_name\":\"{}\",\"lifetime\":\"{:?}\"}}",
+                    escape_json(r.type_name),
+                    r.type_
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let errors = report
+            .errors
+            .iter()
+            .map(|(service, error)| {
+                format!(
+                    "{{\"service\":\"{}\",\"error\":\"{}\"}}",
+                    escape_json(service),
+                    escape_json(error)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!(
+            "{{\"registrations\":[{registrations}],\"singletons\":[{}],\"live_scopes\":{},\"recent_errors\":[{errors}]}}",
+            strings(&report.singletons),
+            report.live_scopes,
+        )
+    }
+
+    pub fn html(&self, collection: &ServiceCollection) -> String {
+        let report = Report::new(self, collection);
+        let mut html = String::from("<!DOCTYPE html><html><head><title>deppy</title></head><body>");
+
+        html.push_str("<h1>Registrations</h1><table><tr><th>Service</th><th>Lifetime</th></tr>");
+        // Writing to a `String` can't fail
+        for registration in &report.registrations {
+            let _ = write!(
+                html,
+                "<tr><td>{}</td><td>{:?}</td></tr>",
+                escape_html(registration.type_name),
+                registration.type_
+            );
+        }
+        html.push_str("</table><h1>Singletons</h1><ul>");
+        for singleton in &report.singletons {
+            let _ = write!(html, "<li>{}</li>", escape_html(singleton));
+        }
+        let _ = write!(
+            html,
+            "</ul><h1>Live scopes</h1><p>{}</p><h1>Recent errors</h1><ul>",
+            report.live_scopes
+        );
+        for (service, error) in &report.errors {
+            let _ = write!(
+                html,
+                "<li>{}: {}</li>",
+                escape_html(service),
+                escape_html(error)
+            );
+        }
+        html.push_str("</ul></body></html>");
+        html
+    }
+}
+
+struct Report {
+    registrations: Vec<RegistrationSummary>,
+    singletons: Vec<&'static str>,
+    live_scopes: usize,
+    errors: Vec<(&'static str, String)>,
+}
+
+impl Report {
+    fn new(endpoint: &DebugEndpoint, collection: &ServiceCollection) -> Self {
+        let services = collection.service_info.snapshot();
+        let registrations: Vec<_> = services
+            .iter()
+            .map(|(_, information)| RegistrationSummary::from(information))
+            .collect();
+
+        let mut singletons: Vec<_> = collection.singletons_iter().map(|(name, _)| name).collect();
+        singletons.sort_unstable();
+
+        Self {
+            registrations,
+            singletons,
+            live_scopes: endpoint.state.live_scopes.load(Ordering::Relaxed),
+            errors: endpoint
+                .state
+                .errors
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+fn escape_json(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl ServiceCollectionBuilder {
+    /// Lets `endpoint` track the scopes and resolution errors of the collection
+    pub fn debug_endpoint(mut self, endpoint: &DebugEndpoint) -> Self {
+        let state = endpoint.state.clone();
+        self.observers.push(endpoint.state.clone());
+
+        self.use_resolver(move |context, next| {
+            let value = next.run();
+            if let Err(error) = &value {
+                let mut errors = state.errors.lock().unwrap_or_else(PoisonError::into_inner);
+                if errors.len() == RECENT_ERRORS {
+                    errors.pop_front();
+                }
+                errors.push_back((context.type_name.unwrap_or("unknown"), error.to_string()));
+            }
+            value
+        })
+    }
+}
@@ -0,0 +1,24 @@
+use crate::{ServiceCollectionBuilder, ServiceInformation};
+
+impl ServiceCollectionBuilder {
+    /// Marks the last registered service as deprecated.
+    /// The service is listed as deprecated by `startup_report` and `dump_graph`, and with the `tracing`
+    /// feature a warning with `message`, e.g. "use NewMailer", is logged the first time it's resolved
+    pub fn deprecated(mut self, message: &'static str) -> Self {
+        self.last_registration().deprecated = Some(message);
+        self
+    }
+}
+
+/// Called once per container, when the service is resolved for the first time
+pub(crate) fn warn_deprecated(information: &ServiceInformation) {
+    #[cfg(feature = "tracing")]
+    if let Some(message) = information.deprecated {
+        tracing::warn!(
+            service = information.type_name,
+            "Resolved deprecated service: {message}"
+        );
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = information;
+}
@@ -0,0 +1,78 @@
+//! diesel r2d2 pools as singletons and a pooled connection per scope, e.g.
+//! `builder.add_diesel_pool::<PgConnection>(url, |b| b.max_size(8)).add_diesel_connection::<PgConnection>()`
+//! registers a `Pool<ConnectionManager<PgConnection>>` and a `DbConnection<PgConnection>`
+//! that returns its connection to the pool when the scope is closed
+
+use crate::{AsyncDispose, BoxFuture, Dep, ServiceCollectionBuilder, ServiceHandler};
+use ::diesel::r2d2::{
+    Builder, ConnectionManager, Pool, PoolError, PooledConnection, R2D2Connection,
+};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Connection of a scope, checked out of the pool on the first call to `with`.
+/// `ServiceScope::close` returns it to the pool, as does dropping the scope
+pub struct DbConnection<C: R2D2Connection + 'static> {
+    pool: Dep<Pool<ConnectionManager<C>>>,
+    connection: Mutex<Option<PooledConnection<ConnectionManager<C>>>>,
+}
+
+impl<C: R2D2Connection + 'static> DbConnection<C> {
+    /// Calls `f` with the connection, checking it out if it's the first use in the scope or after `release`.
+    /// Blocks until a connection is available or the pool's connection timeout elapses
+    pub fn with<R>(&self, f: impl FnOnce(&mut C) -> R) -> Result<R, PoolError> {
+        let mut connection = self
+            .connection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let connection = match &mut *connection {
+            Some(connection) => connection,
+            None => connection.insert(self.pool.get()?),
+        };
+        Ok(f(connection))
+    }
+
+    /// Returns the connection to the pool if one was checked out
+    pub fn release(&self) {
+        self.connection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+    }
+}
+
+impl<C: R2D2Connection + 'static> AsyncDispose for DbConnection<C> {
+    fn dispose(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async move { self.release() })
+    }
+}
+
+impl ServiceCollectionBuilder {
+    /// Registers a pool connecting to `url` as a singleton, configured by `configure`,
+    /// e.g. `add_diesel_pool::<PgConnection>(url, |b| b.max_size(8))`.
+    /// The pool is built on first resolution, on the blocking thread pool when resolved asynchronously
+    pub fn add_diesel_pool<C: R2D2Connection + 'static>(
+        self,
+        url: impl Into<Arc<str>>,
+        configure: impl Fn(Builder<ConnectionManager<C>>) -> Builder<ConnectionManager<C>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        let url = url.into();
+        self.add_singleton_with(move |_| {
+            configure(Pool::builder()).build(ConnectionManager::<C>::new(&*url))
+        })
+        .blocking()
+    }
+
+    /// Registers `DbConnection<C>` as a scoped service using the pool registered with `add_diesel_pool`
+    pub fn add_diesel_connection<C: R2D2Connection + 'static>(self) -> Self {
+        self.add_scoped_with(|provider| {
+            Ok::<_, crate::Error>(DbConnection::<C> {
+                pool: provider.try_get_service()?,
+                connection: Mutex::new(None),
+            })
+        })
+        .async_dispose::<DbConnection<C>>()
+    }
+}
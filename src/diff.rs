@@ -0,0 +1,83 @@
+use crate::{ServiceCollection, ServiceInformation, ServiceType};
+
+/// How a registration constructs its service
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitializerKind {
+    Sync,
+    Async,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistrationSummary {
+    pub type_name: &'static str,
+    pub type_: ServiceType,
+    pub initializer: InitializerKind,
+    /// Concrete type behind a trait object registration, if known
+    pub implementation: Option<&'static str>,
+    /// Message given to `deprecated`
+    pub deprecated: Option<&'static str>,
+}
+
+impl From<&ServiceInformation> for RegistrationSummary {
+    fn from(value: &ServiceInformation) -> Self {
+        Self {
+            type_name: value.type_name,
+            type_: value.type_.clone(),
+            initializer: if value.initialize_async_fn.is_some() {
+                InitializerKind::Async
+            } else {
+                InitializerKind::Sync
+            },
+            implementation: value
+                .implementation
+                .as_ref()
+                .map(|i| i.dependency.type_name),
+            deprecated: value.deprecated,
+        }
+    }
+}
+
+/// Differences between the registrations of two collections, sorted by type name
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ContainerDiff {
+    pub added: Vec<RegistrationSummary>,
+    pub removed: Vec<RegistrationSummary>,
+    /// Registrations of the same type with a different lifetime or initializer, as `(old, new)`
+    pub changed: Vec<(RegistrationSummary, RegistrationSummary)>,
+}
+
+impl ContainerDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl ServiceCollection {
+    /// Compares the registrations of `self` against `other`,
+    /// treating `self` as the old and `other` as the new state
+    pub fn diff(&self, other: &ServiceCollection) -> ContainerDiff {
+        let old = self.service_info.snapshot();
+        let new = other.service_info.snapshot();
+        let mut diff = ContainerDiff::default();
+
+        for (type_id, information) in new.iter() {
+            let summary = RegistrationSummary::from(information);
+            match old.get(type_id).map(RegistrationSummary::from) {
+                None => diff.added.push(summary),
+                Some(previous) if previous != summary => diff.changed.push((previous, summary)),
+                Some(_) => {}
+            }
+        }
+
+        diff.removed = old
+            .iter()
+            .filter(|(type_id, _)| !new.contains_key(type_id))
+            .map(|(_, information)| RegistrationSummary::from(information))
+            .collect();
+
+        diff.added.sort_by_key(|s| s.type_name);
+        diff.removed.sort_by_key(|s| s.type_name);
+        diff.changed.sort_by_key(|(s, _)| s.type_name);
+        diff
+    }
+}
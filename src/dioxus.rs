@@ -0,0 +1,28 @@
+//! Exposes a `ServiceScope` through Dioxus' context system
+
+use crate::{Dep, ServiceHandler, ServiceScope};
+use dioxus_core::{consume_context, provide_context, try_consume_context, use_hook};
+use std::any::Any;
+
+/// Provides the scope to the current component and its children.
+/// The closure only runs on the first render, passing a `ServiceCollection` creates a new scope from it
+pub fn use_scope_provider<S: Into<ServiceScope>>(scope: impl FnOnce() -> S) -> ServiceScope {
+    use_hook(|| provide_context(scope().into()))
+}
+
+/// Returns the scope provided by `use_scope_provider`.
+/// Panics if no scope was provided by a parent component
+pub fn use_scope() -> ServiceScope {
+    use_hook(consume_context)
+}
+
+/// Resolves `T` from the provided scope once and returns the same value on every render.
+/// Panics if no scope was provided or `T` isn't registered
+pub fn use_service<T: Any + Send + Sync>() -> Dep<T> {
+    use_hook(|| ServiceHandler::get_required_service(&consume_context::<ServiceScope>()))
+}
+
+/// Like `use_service`, but returns `None` if there is no scope or `T` isn't registered
+pub fn try_use_service<T: Any + Send + Sync>() -> Option<Dep<T>> {
+    use_hook(|| ServiceHandler::get_service(&try_consume_context::<ServiceScope>()?))
+}
@@ -0,0 +1,65 @@
+use crate::{BoxFuture, ServiceCollectionBuilder, ServiceScope};
+use std::any::Any;
+use std::sync::Arc;
+
+/// Asynchronous cleanup of a scoped service, e.g. flushing buffered messages at the end of a request.
+/// Run by `ServiceScope::close` for services registered with `async_dispose`
+pub trait AsyncDispose: Send + Sync {
+    fn dispose(&self) -> BoxFuture<'_, ()>;
+}
+
+pub(crate) type AsyncDisposeFn = fn(Arc<dyn Any + Send + Sync>) -> BoxFuture<'static, ()>;
+
+impl ServiceCollectionBuilder {
+    /// Has `ServiceScope::close` dispose the last registered service, which has to be `T`
+    pub fn async_dispose<T: AsyncDispose + Any>(mut self) -> Self {
+        self.last_registration().async_dispose = Some(|value| {
+            Box::pin(async move {
+                if let Ok(service) = value.downcast::<T>() {
+                    service.dispose().await;
+                }
+            })
+        });
+        self
+    }
+}
+
+impl ServiceScope {
+    /// Cancels the scope's `cancellation_token` and releases its scoped services, keyed ones included,
+    /// awaiting `AsyncDispose::dispose` of the ones registered with `async_dispose`
+    /// in the reverse order they were initialized, so services are disposed before their dependencies.
+    /// Clones of the scope resolve new instances afterwards
+    pub async fn close(self) {
+        self.lifecycle.cancellation_token.cancel();
+
+        let services = self.services.snapshot();
+        let mut scoped: Vec<_> = self
+            .scoped
+            .take()
+            .into_iter()
+            .filter_map(|(initialized, type_id, value)| {
+                Some((initialized, services.get(&type_id)?.async_dispose?, value))
+            })
+            .collect();
+        scoped.extend(self.keyed_scoped.take().into_iter().filter_map(
+            |(index, (initialized, value))| {
+                Some((
+                    initialized,
+                    self.keyed.registration(index)?.async_dispose?,
+                    value,
+                ))
+            },
+        ));
+        scoped.sort_by_key(|(initialized, _, _)| *initialized);
+
+        // Collected first so the future stays `Send`, e.g. when closing from a Rocket fairing
+        let disposals: Vec<_> = scoped
+            .into_iter()
+            .rev()
+            .map(|(_, dispose, value)| dispose(value))
+            .collect();
+        for dispose in disposals {
+            dispose.await;
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use crate::diff::InitializerKind;
+use crate::validation::is_built_in;
+use crate::{RegistrationSummary, ServiceCollection};
+use std::fmt::Write;
+
+impl ServiceCollection {
+    /// Renders the registrations and their declared dependencies as text, e.g. for snapshot tests.
+    /// Services and dependencies are sorted by type name, so the output only changes with the wiring
+    /// and not with the order of registration
+    pub fn dump_graph(&self) -> String {
+        let services = self.service_info.snapshot();
+        let mut registrations: Vec<_> = services.iter().collect();
+        registrations.sort_by_key(|(_, information)| information.type_name);
+
+        let mut output = String::new();
+        for (_, information) in registrations {
+            let summary = RegistrationSummary::from(information);
+            let initializer = match summary.initializer {
+                InitializerKind::Sync => "sync",
+                InitializerKind::Async => "async",
+            };
+            let _ = write!(
+                output,
+                "{} ({:?}, {initializer})",
+                summary.type_name, summary.type_
+            );
+            if let Some(implementation) = summary.implementation {
+                let _ = write!(output, " implemented by {implementation}");
+            }
+            if summary.deprecated.is_some() {
+                output.push_str(" deprecated");
+            }
+            output.push('\n');
+
+            let mut dependencies: Vec<_> = information.dependencies.iter().collect();
+            dependencies.sort_by_key(|dependency| dependency.type_name);
+            dependencies.dedup();
+            for dependency in dependencies {
+                let _ = write!(output, "  -> {}", dependency.type_name);
+                if services.get(&dependency.type_id).is_none() && !is_built_in(&dependency.type_id)
+                {
+                    output.push_str(" (not registered)");
+                }
+                output.push('\n');
+            }
+        }
+
+        output
+    }
+}
@@ -0,0 +1,93 @@
+use crate::factory::factory_information;
+use crate::trait_object::TraitObject;
+use crate::{
+    BuildOptions, Error, ServiceCollection, ServiceCollectionBuilder, ServiceInformation,
+    ServiceProvider, ServiceType,
+};
+use std::any::TypeId;
+use std::sync::Arc;
+
+/// Creates the implementation of a trait object for one environment, e.g. `S3Storage::init`
+pub type EnvInitializer<T> = fn(ServiceProvider) -> Arc<T>;
+
+/// A service registered with `add_*_per_env`, as `(type name, [(env, registration)])`
+pub(crate) type PerEnv = (&'static str, Vec<(&'static str, ServiceInformation)>);
+
+impl ServiceCollectionBuilder {
+    /// Registers the trait object `T` with one initializer per environment.
+    /// The initializer is picked by the environment set with `env` or passed to `build_with_env`,
+    /// building fails with `Error::UnmatchedEnvironment` if none matches
+    pub fn add_service_per_env<T: ?Sized + Send + Sync + 'static>(
+        mut self,
+        type_: ServiceType,
+        initializers: &[(&'static str, EnvInitializer<T>)],
+    ) -> Self {
+        let candidates = initializers
+            .iter()
+            .map(|&(env, init)| {
+                let mut information = factory_information(type_.clone(), move |provider| {
+                    Ok(TraitObject::from_object(init(provider)))
+                });
+                information.type_name = std::any::type_name::<T>();
+                (env, information)
+            })
+            .collect();
+
+        self.per_env
+            .insert(TypeId::of::<T>(), (std::any::type_name::<T>(), candidates));
+        // Options like `memoize` can't be applied before the environment is known
+        self.last_registered = None;
+
+        self
+    }
+
+    pub fn add_singleton_per_env<T: ?Sized + Send + Sync + 'static>(
+        self,
+        initializers: &[(&'static str, EnvInitializer<T>)],
+    ) -> Self {
+        self.add_service_per_env(ServiceType::Singleton, initializers)
+    }
+
+    pub fn add_scoped_per_env<T: ?Sized + Send + Sync + 'static>(
+        self,
+        initializers: &[(&'static str, EnvInitializer<T>)],
+    ) -> Self {
+        self.add_service_per_env(ServiceType::Scoped, initializers)
+    }
+
+    pub fn add_transient_per_env<T: ?Sized + Send + Sync + 'static>(
+        self,
+        initializers: &[(&'static str, EnvInitializer<T>)],
+    ) -> Self {
+        self.add_service_per_env(ServiceType::Transient, initializers)
+    }
+
+    /// Sets the environment, e.g. "prod", whose initializers registered through `add_*_per_env`
+    /// are used by every way of building the collection
+    pub fn env(mut self, env: impl Into<String>) -> Self {
+        self.env = Some(env.into());
+        self
+    }
+
+    /// Builds the collection with the initializers registered for `env` through `add_*_per_env`,
+    /// like `build_with` with the default options
+    pub fn build_with_env(self, env: &str) -> Result<ServiceCollection, Error> {
+        self.env(env).build_with(BuildOptions::default())
+    }
+
+    /// Registers the initializers of the environment, returning the services without one
+    pub(crate) fn select_env(&mut self) -> Vec<Error> {
+        let mut unmatched = Vec::new();
+        for (type_id, (service, candidates)) in std::mem::take(&mut self.per_env) {
+            let env = self.env.as_deref();
+            match candidates.into_iter().find(|(e, _)| Some(*e) == env) {
+                Some((_, information)) => self.register(type_id, information),
+                None => unmatched.push(Error::UnmatchedEnvironment {
+                    service,
+                    env: self.env.clone(),
+                }),
+            }
+        }
+        unmatched
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The requested service isn't registered.
+    /// `requested_by` is the service whose initializer requested it, if any
+    NotRegistered {
+        type_name: Option<&'static str>,
+        requested_by: Option<&'static str>,
+    },
+    /// The scoped service with the given type name was resolved from the root collection
+    /// with `RootScopePolicy::Error`
+    ScopedFromRoot(&'static str),
+    /// The service with the given type name was registered with `scope_only` but resolved from the collection
+    ScopeOnly(&'static str),
+    /// The service with the given type name was registered with `root_only` but resolved from a scope
+    RootOnly(&'static str),
+    /// The service with the given type name only has an async initializer but was resolved synchronously
+    AsyncOnly(&'static str),
+    /// The resolved value isn't of the requested type with the given name, e.g. because a resolver substituted it
+    TypeMismatch(&'static str),
+    /// A registered service depends on a service that isn't registered
+    MissingDependency {
+        service: &'static str,
+        dependency: &'static str,
+    },
+    /// A service depends on a service with a shorter lifetime, e.g. a singleton on a scoped service
+    WrongLifetimeContext {
+        service: &'static str,
+        dependency: &'static str,
+    },
+    /// The service with the given type name was registered more than once
+    /// and the collection was built with `BuildOptions::fail_on_duplicates`
+    DuplicateRegistration(&'static str),
+    /// Configuration sections bound with `bind_section` that are missing or invalid, as `(key, error)`
+    InvalidConfiguration(Vec<(&'static str, Error)>),
+    /// No service is registered with the given key through `stable_key` or `add_keyed_service`
+    UnknownKey(String),
+    /// None of the initializers the service was registered with through `add_*_per_env` is for `env`,
+    /// or the collection was built without an environment
+    UnmatchedEnvironment {
+        service: &'static str,
+        env: Option<String>,
+    },
+    /// The declared dependencies form a cycle, or a synchronous initializer resolved a service
+    /// whose initialization is still running. The first and last type names of the path are the same
+    Cycle { path: Vec<&'static str> },
+    /// Error returned by an initializer
+    InitializationFailed {
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// A participant of a `UnitOfWork` failed to commit, the participants that weren't committed were rolled back
+    CommitFailed {
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// The handler of a job of the given type failed
+    JobFailed {
+        job: &'static str,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// The initializer of the service with the given type name didn't finish in time
+    Timeout(&'static str),
+    /// The container's cancellation token was triggered while the service was being initialized
+    Cancelled,
+    /// A lock of the container was poisoned by a panicking initializer
+    LockPoisoned,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NotRegistered {
+                type_name,
+                requested_by,
+            } => {
+                match type_name {
+                    Some(type_name) => write!(f, "Service {type_name} couldn't be found")?,
+                    None => write!(f, "Service couldn't be found")?,
+                }
+                match requested_by {
+                    Some(requested_by) => write!(f, " (requested by {requested_by})"),
+                    None => Ok(()),
+                }
+            }
+            Error::ScopedFromRoot(type_name) => write!(
+                f,
+                "Scoped service {type_name} can't be resolved from the root collection, resolve it from a scope"
+            ),
+            Error::ScopeOnly(type_name) => {
+                write!(f, "Service {type_name} can only be resolved from a scope")
+            }
+            Error::RootOnly(type_name) => write!(
+                f,
+                "Service {type_name} can only be resolved from the root collection"
+            ),
+            Error::AsyncOnly(type_name) => {
+                write!(f, "Service {type_name} can only be resolved asynchronously")
+            }
+            Error::TypeMismatch(type_name) => {
+                write!(f, "Resolved service isn't of type {type_name}")
+            }
+            Error::MissingDependency {
+                service,
+                dependency,
+            } => write!(
+                f,
+                "Service {service} depends on {dependency} which isn't registered"
+            ),
+            Error::WrongLifetimeContext {
+                service,
+                dependency,
+            } => write!(f, "Service {service} outlives its dependency {dependency}"),
+            Error::DuplicateRegistration(type_name) => {
+                write!(f, "Service {type_name} is registered more than once")
+            }
+            Error::InvalidConfiguration(errors) => {
+                let errors: Vec<_> = errors
+                    .iter()
+                    .map(|(key, error)| format!("section {key}: {error}"))
+                    .collect();
+                write!(f, "Invalid configuration, {}", errors.join("; "))
+            }
+            Error::UnknownKey(key) => write!(f, "No service is registered with key {key}"),
+            Error::UnmatchedEnvironment { service, env } => match env {
+                Some(env) => write!(f, "Service {service} has no initializer for environment {env}"),
+                None => write!(
+                    f,
+                    "Service {service} is registered per environment but no environment was set"
+                ),
+            },
+            Error::Cycle { path } => {
+                write!(f, "Dependency cycle detected: {}", path.join(" -> "))
+            }
+            Error::InitializationFailed { source } => {
+                write!(f, "Service initialization failed: {source}")
+            }
+            Error::CommitFailed { source } => {
+                write!(f, "Unit of work couldn't be committed: {source}")
+            }
+            Error::JobFailed { job, source } => write!(f, "Job {job} failed: {source}"),
+            Error::Timeout(type_name) => {
+                write!(f, "Initialization of service {type_name} timed out")
+            }
+            Error::Cancelled => write!(f, "Service initialization was cancelled"),
+            Error::LockPoisoned => write!(f, "A lock of the container was poisoned"),
+        }
+    }
+}
+
+impl Error {
+    pub(crate) fn not_registered() -> Self {
+        Error::NotRegistered {
+            type_name: None,
+            requested_by: None,
+        }
+    }
+
+    /// Fills in the requested type name if the resolution by `TypeId` couldn't tell
+    pub(crate) fn with_type_name(mut self, name: &'static str) -> Self {
+        if let Error::NotRegistered { type_name, .. } = &mut self {
+            type_name.get_or_insert(name);
+        }
+        self
+    }
+
+    /// Records the service whose initializer failed to resolve one of its dependencies
+    pub(crate) fn requested_by(mut self, service: &'static str) -> Self {
+        if let Error::NotRegistered { requested_by, .. } = &mut self {
+            requested_by.get_or_insert(service);
+        }
+        self
+    }
+    /// Converts the error into the application's error type `E`.
+    /// An `E` returned by a factory is passed through as it is,
+    /// instead of being wrapped in `Error::InitializationFailed` and converted again
+    pub fn into_app_error<E>(self) -> E
+    where
+        E: From<Error> + std::error::Error + 'static,
+    {
+        match self {
+            Error::InitializationFailed { source } => match source.downcast::<E>() {
+                Ok(error) => *error,
+                Err(source) => E::from(Error::InitializationFailed { source }),
+            },
+            error => E::from(error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InitializationFailed { source }
+            | Error::CommitFailed { source }
+            | Error::JobFailed { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
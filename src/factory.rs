@@ -0,0 +1,187 @@
+use crate::{
+    AsyncResolveFn, AsyncServiceHandler, BoxFuture, Error, InitializeFn, ServiceCollection,
+    ServiceCollectionBuilder, ServiceHandler, ServiceInformation, ServiceScope, ServiceType,
+};
+use std::any::{Any, TypeId};
+use std::error::Error as StdError;
+use std::sync::Arc;
+
+/// Handler passed to factory closures, which can't be generic over the handler type
+#[derive(Clone, Copy)]
+pub enum ServiceProvider<'a> {
+    Collection(&'a ServiceCollection),
+    Scope(&'a ServiceScope),
+}
+
+/// Errors of the container returned by a factory are passed through as they are,
+/// e.g. when it used `?` on `try_get_service`
+pub(crate) fn into_error(error: Box<dyn StdError + Send + Sync>) -> Error {
+    match error.downcast::<Error>() {
+        Ok(error) => *error,
+        Err(source) => Error::InitializationFailed { source },
+    }
+}
+
+impl ServiceHandler for ServiceProvider<'_> {
+    type ScopeType = ServiceScope;
+
+    fn get_service_by_type_id(&self, type_id: &TypeId) -> Option<Arc<dyn Any + Send + Sync>> {
+        match self {
+            Self::Collection(c) => c.get_service_by_type_id(type_id),
+            Self::Scope(s) => s.get_service_by_type_id(type_id),
+        }
+    }
+
+    fn try_get_service_by_type_id(
+        &self,
+        type_id: &TypeId,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        match self {
+            Self::Collection(c) => c.try_get_service_by_type_id(type_id),
+            Self::Scope(s) => s.try_get_service_by_type_id(type_id),
+        }
+    }
+
+    fn create_scope(&self) -> Self::ScopeType
+    where
+        Self::ScopeType: ServiceHandler,
+    {
+        match self {
+            Self::Collection(c) => c.create_scope(),
+            Self::Scope(s) => s.create_scope(),
+        }
+    }
+
+    fn try_get_keyed_service_by_type_id(
+        &self,
+        type_id: &TypeId,
+        key: &str,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        match self {
+            Self::Collection(c) => c.try_get_keyed_service_by_type_id(type_id, key),
+            Self::Scope(s) => s.try_get_keyed_service_by_type_id(type_id, key),
+        }
+    }
+
+    fn get_services_by_type_id(
+        &self,
+        type_id: &TypeId,
+    ) -> Vec<Result<Arc<dyn Any + Send + Sync>, Error>> {
+        match self {
+            Self::Collection(c) => c.get_services_by_type_id(type_id),
+            Self::Scope(s) => s.get_services_by_type_id(type_id),
+        }
+    }
+
+    fn registered_type_names(&self) -> Vec<&'static str> {
+        match self {
+            Self::Collection(c) => c.registered_type_names(),
+            Self::Scope(s) => s.registered_type_names(),
+        }
+    }
+
+    fn async_resolver(&self) -> Option<AsyncResolveFn> {
+        match self {
+            Self::Collection(c) => c.async_resolver(),
+            Self::Scope(s) => s.async_resolver(),
+        }
+    }
+}
+
+impl AsyncServiceHandler for ServiceProvider<'_> {
+    fn get_async_service_by_type_id<'a>(
+        &'a self,
+        type_id: &'a TypeId,
+    ) -> BoxFuture<'a, Result<Arc<dyn Any + Send + Sync>, Error>> {
+        match self {
+            Self::Collection(c) => c.get_async_service_by_type_id(type_id),
+            Self::Scope(s) => s.get_async_service_by_type_id(type_id),
+        }
+    }
+}
+
+/// Registration of a service created by a closure, shared by `add_service_with` and `add_service_per_env`
+pub(crate) fn factory_information<T, F>(type_: ServiceType, factory: F) -> ServiceInformation
+where
+    T: Any + Send + Sync,
+    F: Fn(ServiceProvider) -> Result<T, Error> + Send + Sync + 'static,
+{
+    let factory = Arc::new(factory);
+    let collection_factory = factory.clone();
+    let collection_closure: InitializeFn<ServiceCollection> = Arc::new(move |x, _| {
+        collection_factory(ServiceProvider::Collection(x)).map(|v| Box::new(v) as _)
+    });
+    let scoped_closure: InitializeFn<ServiceScope> =
+        Arc::new(move |x, _| factory(ServiceProvider::Scope(x)).map(|v| Box::new(v) as _));
+
+    ServiceInformation {
+        initialize_fn: Some(collection_closure),
+        scoped_initialize_fn: Some(scoped_closure),
+        initialize_async_fn: None,
+        scoped_initialize_async_fn: None,
+        type_,
+        type_name: std::any::type_name::<T>(),
+        memoize: None,
+        timeout: None,
+        retry: None,
+        data: Default::default(),
+        receives_data: false,
+        dependencies: Vec::new(),
+        implementation: None,
+        deprecated: None,
+        memory_footprint: None,
+        async_dispose: None,
+        placement: Default::default(),
+        concurrency: None,
+        blocking: false,
+        stable_key: None,
+    }
+}
+
+impl ServiceCollectionBuilder {
+    /// Registers a service created by a closure.
+    /// An error returned by the closure is surfaced as `Error::InitializationFailed` by `try_get_service`
+    /// and the async resolution methods, the other methods treat the service as not found
+    pub fn add_service_with<T, E, F>(mut self, type_: ServiceType, factory: F) -> Self
+    where
+        T: Any + Send + Sync,
+        E: Into<Box<dyn StdError + Send + Sync>>,
+        F: Fn(ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+    {
+        let information = factory_information(type_, move |provider| {
+            factory(provider).map_err(|e| into_error(e.into()))
+        });
+
+        self.register(TypeId::of::<T>(), information);
+        self.last_registered = Some(TypeId::of::<T>());
+
+        self
+    }
+
+    pub fn add_singleton_with<T, E, F>(self, factory: F) -> Self
+    where
+        T: Any + Send + Sync,
+        E: Into<Box<dyn StdError + Send + Sync>>,
+        F: Fn(ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+    {
+        self.add_service_with(ServiceType::Singleton, factory)
+    }
+
+    pub fn add_scoped_with<T, E, F>(self, factory: F) -> Self
+    where
+        T: Any + Send + Sync,
+        E: Into<Box<dyn StdError + Send + Sync>>,
+        F: Fn(ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+    {
+        self.add_service_with(ServiceType::Scoped, factory)
+    }
+
+    pub fn add_transient_with<T, E, F>(self, factory: F) -> Self
+    where
+        T: Any + Send + Sync,
+        E: Into<Box<dyn StdError + Send + Sync>>,
+        F: Fn(ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+    {
+        self.add_service_with(ServiceType::Transient, factory)
+    }
+}
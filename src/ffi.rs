@@ -0,0 +1,119 @@
+//! `extern "C"` functions that let C and C++ hosts create scopes and resolve services by the keys
+//! assigned with `stable_key`. Every handle is opaque to the host and has to be released with its
+//! `deppy_*_free` function. Panics, e.g. from initializers, never unwind into the host,
+//! the functions return null instead
+
+use crate::{ServiceCollection, ServiceHandler, ServiceScope};
+use std::any::Any;
+use std::ffi::{c_char, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+
+/// Collection handed to the host
+pub struct FfiCollection {
+    collection: ServiceCollection,
+}
+
+pub struct FfiScope {
+    scope: ServiceScope,
+}
+
+/// Resolved service, keeps the service alive until `deppy_service_free`
+pub struct FfiService {
+    value: Arc<dyn Any + Send + Sync>,
+}
+
+impl FfiCollection {
+    pub fn new(collection: ServiceCollection) -> Self {
+        Self { collection }
+    }
+
+    /// Hands the collection to the host, which releases it with `deppy_collection_free`
+    pub fn into_raw(self) -> *mut FfiCollection {
+        Box::into_raw(Box::new(self))
+    }
+}
+
+impl FfiService {
+    /// The service behind the handle, for Rust functions the host calls with it
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.value.downcast_ref::<T>()
+    }
+}
+
+/// Runs `f`, a panic is swallowed so it doesn't unwind across the boundary
+fn guarded<T>(fallback: T, f: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+/// # Safety
+/// `collection` has to come from `FfiCollection::into_raw` and must not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn deppy_collection_free(collection: *mut FfiCollection) {
+    if !collection.is_null() {
+        guarded((), || drop(Box::from_raw(collection)));
+    }
+}
+
+/// Creates a scope, returns null if `collection` is null
+///
+/// # Safety
+/// `collection` has to be null or a live handle from `FfiCollection::into_raw`
+#[no_mangle]
+pub unsafe extern "C" fn deppy_scope_create(collection: *const FfiCollection) -> *mut FfiScope {
+    let Some(collection) = collection.as_ref() else {
+        return std::ptr::null_mut();
+    };
+
+    guarded(std::ptr::null_mut(), || {
+        Box::into_raw(Box::new(FfiScope {
+            scope: collection.collection.create_scope(),
+        }))
+    })
+}
+
+/// # Safety
+/// `scope` has to come from `deppy_scope_create` and must not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn deppy_scope_free(scope: *mut FfiScope) {
+    if !scope.is_null() {
+        guarded((), || drop(Box::from_raw(scope)));
+    }
+}
+
+/// Resolves the service registered with the stable `key` from the scope.
+/// Returns null if no service has the key or it couldn't be resolved
+///
+/// # Safety
+/// `scope` has to be null or a live handle, `key` null or a nul-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn deppy_resolve(
+    scope: *const FfiScope,
+    key: *const c_char,
+) -> *mut FfiService {
+    let Some(scope) = scope.as_ref() else {
+        return std::ptr::null_mut();
+    };
+    if key.is_null() {
+        return std::ptr::null_mut();
+    }
+    let Ok(key) = CStr::from_ptr(key).to_str() else {
+        return std::ptr::null_mut();
+    };
+
+    guarded(std::ptr::null_mut(), || {
+        match scope.scope.get_service_by_key(key) {
+            Ok(value) => Box::into_raw(Box::new(FfiService { value })),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// # Safety
+/// `service` has to come from `deppy_resolve` and must not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn deppy_service_free(service: *mut FfiService) {
+    if !service.is_null() {
+        guarded((), || drop(Box::from_raw(service)));
+    }
+}
@@ -0,0 +1,51 @@
+//! Layered configuration through `figment`, e.g. a file merged with environment variables and CLI arguments
+
+use crate::{Error, ServiceCollection, ServiceCollectionBuilder, ServiceHandler};
+use ::figment::Figment;
+use serde::de::DeserializeOwned;
+use std::any::{Any, TypeId};
+use std::convert::Infallible;
+use std::error::Error as StdError;
+
+impl ServiceCollectionBuilder {
+    /// Registers `figment` as the configuration source, resolvable as `Dep<Figment>`
+    pub fn add_figment(self, figment: Figment) -> Self {
+        self.add_singleton_with(move |_| Ok::<_, Infallible>(figment.clone()))
+    }
+
+    /// Registers the section of the configuration at `key`, e.g. `"database"`, as the singleton `T`.
+    /// `build_with` deserializes every bound section and reports all missing or invalid ones at once
+    /// with `Error::InvalidConfiguration`. With `build` the section is deserialized on first resolution
+    /// and an error is surfaced as `Error::InitializationFailed` by `try_get_service`
+    pub fn bind_section<T: DeserializeOwned + Any + Send + Sync>(
+        mut self,
+        key: &'static str,
+    ) -> Self {
+        self.sections.push((key, TypeId::of::<T>()));
+        self.add_singleton_with(
+            move |provider| -> Result<T, Box<dyn StdError + Send + Sync>> {
+                Ok(provider.try_get_service::<Figment>()?.extract_inner(key)?)
+            },
+        )
+    }
+}
+
+/// Deserializes every section bound with `bind_section`, collecting the errors
+pub(crate) fn extract_sections(
+    collection: &ServiceCollection,
+    sections: &[(&'static str, TypeId)],
+) -> Result<(), Error> {
+    let errors: Vec<_> = sections
+        .iter()
+        .filter_map(|(key, type_id)| {
+            let error = collection.try_get_service_by_type_id(type_id).err()?;
+            Some((*key, error))
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::InvalidConfiguration(errors))
+    }
+}
@@ -0,0 +1,71 @@
+//! `ServiceHandler` and `AsyncServiceHandler` for references and smart pointers to handlers,
+//! so functions generic over the handler accept `&collection` or `Arc<ServiceScope>` without cloning
+
+use crate::{AsyncResolveFn, AsyncServiceHandler, BoxFuture, Error, ServiceHandler};
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+macro_rules! forward_handler {
+    ($($pointer:ty),+) => {
+        $(
+            impl<H: ServiceHandler + ?Sized> ServiceHandler for $pointer {
+                type ScopeType = H::ScopeType;
+
+                fn get_service_by_type_id(
+                    &self,
+                    type_id: &TypeId,
+                ) -> Option<Arc<dyn Any + Send + Sync>> {
+                    (**self).get_service_by_type_id(type_id)
+                }
+
+                fn try_get_service_by_type_id(
+                    &self,
+                    type_id: &TypeId,
+                ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+                    (**self).try_get_service_by_type_id(type_id)
+                }
+
+                fn create_scope(&self) -> Self::ScopeType
+                where
+                    Self::ScopeType: ServiceHandler,
+                {
+                    (**self).create_scope()
+                }
+
+                fn try_get_keyed_service_by_type_id(
+                    &self,
+                    type_id: &TypeId,
+                    key: &str,
+                ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+                    (**self).try_get_keyed_service_by_type_id(type_id, key)
+                }
+
+                fn get_services_by_type_id(
+                    &self,
+                    type_id: &TypeId,
+                ) -> Vec<Result<Arc<dyn Any + Send + Sync>, Error>> {
+                    (**self).get_services_by_type_id(type_id)
+                }
+
+                fn registered_type_names(&self) -> Vec<&'static str> {
+                    (**self).registered_type_names()
+                }
+
+                fn async_resolver(&self) -> Option<AsyncResolveFn> {
+                    (**self).async_resolver()
+                }
+            }
+
+            impl<H: AsyncServiceHandler + Send + ?Sized> AsyncServiceHandler for $pointer {
+                fn get_async_service_by_type_id<'a>(
+                    &'a self,
+                    type_id: &'a TypeId,
+                ) -> BoxFuture<'a, Result<Arc<dyn Any + Send + Sync>, Error>> {
+                    (**self).get_async_service_by_type_id(type_id)
+                }
+            }
+        )+
+    };
+}
+
+forward_handler!(&H, Arc<H>, Box<H>);
@@ -0,0 +1,34 @@
+use crate::{Dep, Error, ServiceHandler, ServiceScope};
+use std::any::Any;
+
+/// Values the framework integrations can resolve from the scope of a request or command.
+/// Tuples resolve every element from the same scope
+pub trait FromScope: Sized {
+    fn from_scope(scope: &ServiceScope) -> Result<Self, Error>;
+}
+
+impl<T: Any + Send + Sync> FromScope for Dep<T> {
+    fn from_scope(scope: &ServiceScope) -> Result<Self, Error> {
+        scope.try_get_service()
+    }
+}
+
+impl FromScope for ServiceScope {
+    fn from_scope(scope: &ServiceScope) -> Result<Self, Error> {
+        Ok(scope.clone())
+    }
+}
+
+macro_rules! impl_from_scope_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: FromScope),+> FromScope for ($($name,)+) {
+            fn from_scope(scope: &ServiceScope) -> Result<Self, Error> {
+                Ok(($($name::from_scope(scope)?,)+))
+            }
+        }
+    };
+}
+
+impl_from_scope_tuple!(A, B);
+impl_from_scope_tuple!(A, B, C);
+impl_from_scope_tuple!(A, B, C, D);
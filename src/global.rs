@@ -0,0 +1,37 @@
+//! Process-wide container for CLIs and plugins that can't pass a handler through every call path.
+//! Prefer passing the handler where possible, services resolved from here are hidden dependencies
+
+use crate::{Dep, ServiceCollection, ServiceHandler, ServiceScope};
+use std::any::Any;
+use std::sync::OnceLock;
+
+static COLLECTION: OnceLock<ServiceCollection> = OnceLock::new();
+
+/// Installs the global collection.
+/// Returns `collection` back if a collection was already installed
+pub fn init(collection: ServiceCollection) -> Result<(), ServiceCollection> {
+    COLLECTION.set(collection)
+}
+
+/// The collection installed by `init`, `None` before it's installed
+pub fn try_collection() -> Option<&'static ServiceCollection> {
+    COLLECTION.get()
+}
+
+/// The collection installed by `init`.
+/// Panics if it hasn't been installed yet
+pub fn collection() -> &'static ServiceCollection {
+    try_collection().expect("The global collection has to be installed with global::init first")
+}
+
+/// Resolves `T` from the global collection.
+/// Panics if the collection hasn't been installed or `T` can't be resolved
+pub fn get<T: Any + Send + Sync>() -> Dep<T> {
+    collection().get_required_service()
+}
+
+/// Creates a scope of the global collection.
+/// Panics if the collection hasn't been installed
+pub fn scope() -> ServiceScope {
+    collection().create_scope()
+}
@@ -0,0 +1,116 @@
+use crate::type_map::TypeMap;
+use crate::{ServiceCollection, ServiceInformation};
+use std::any::TypeId;
+use std::collections::{HashMap, HashSet};
+
+/// Coupling of a single service, see `ServiceCollection::graph_metrics`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceMetrics {
+    pub type_name: &'static str,
+    /// Length of the longest chain of registered dependencies below the service, 0 if it has none
+    pub depth: usize,
+    /// Number of registered services declaring the service as a dependency
+    pub fan_in: usize,
+    /// Number of dependencies the service declares, registered or not
+    pub fan_out: usize,
+}
+
+/// Shape of the declared dependency graph, see `ServiceCollection::graph_metrics`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphMetrics {
+    /// Every registered service, in registration order
+    pub services: Vec<ServiceMetrics>,
+    /// Type names of the longest construction chain, starting with the service constructed last
+    pub longest_chain: Vec<&'static str>,
+}
+
+impl GraphMetrics {
+    /// The service with the most dependents, if any service is registered
+    pub fn max_fan_in(&self) -> Option<&ServiceMetrics> {
+        self.services.iter().max_by_key(|s| s.fan_in)
+    }
+
+    /// The service with the most dependencies, if any service is registered
+    pub fn max_fan_out(&self) -> Option<&ServiceMetrics> {
+        self.services.iter().max_by_key(|s| s.fan_out)
+    }
+}
+
+/// Depth of `root`, the edges of a cycle aren't followed
+fn depth(
+    services: &TypeMap<ServiceInformation>,
+    root: &TypeId,
+    depths: &mut HashMap<TypeId, usize>,
+    path: &mut HashSet<TypeId>,
+) -> usize {
+    if let Some(depth) = depths.get(root) {
+        return *depth;
+    }
+    let Some(information) = services.get(root) else {
+        return 0;
+    };
+
+    path.insert(*root);
+    let mut max = 0;
+    for dependency in &information.dependencies {
+        if services.get(&dependency.type_id).is_some() && !path.contains(&dependency.type_id) {
+            max = max.max(depth(services, &dependency.type_id, depths, path) + 1);
+        }
+    }
+    path.remove(root);
+
+    depths.insert(*root, max);
+    max
+}
+
+impl ServiceCollection {
+    /// Measures the dependency graph declared through `Injectable::dependencies`,
+    /// to find services that are coupled to many others or sit at the top of long construction chains
+    pub fn graph_metrics(&self) -> GraphMetrics {
+        let services = self.service_info.snapshot();
+        let mut depths = HashMap::new();
+        let mut fan_in: HashMap<TypeId, usize> = HashMap::new();
+        for (_, information) in services.iter() {
+            let dependencies: HashSet<_> = information.dependencies.iter().collect();
+            for dependency in dependencies {
+                *fan_in.entry(dependency.type_id).or_default() += 1;
+            }
+        }
+
+        let mut metrics = GraphMetrics::default();
+        let mut deepest: Option<(TypeId, usize)> = None;
+        for (type_id, information) in services.iter() {
+            let depth = depth(&services, type_id, &mut depths, &mut HashSet::new());
+            if deepest.is_none_or(|(_, max)| depth > max) {
+                deepest = Some((*type_id, depth));
+            }
+            metrics.services.push(ServiceMetrics {
+                type_name: information.type_name,
+                depth,
+                fan_in: fan_in.get(type_id).copied().unwrap_or(0),
+                fan_out: information
+                    .dependencies
+                    .iter()
+                    .collect::<HashSet<_>>()
+                    .len(),
+            });
+        }
+
+        // Follows the deepest dependency from the deepest service down to a leaf
+        let mut current = deepest.map(|(type_id, _)| type_id);
+        while let Some(type_id) = current {
+            let Some(information) = services.get(&type_id) else {
+                break;
+            };
+            metrics.longest_chain.push(information.type_name);
+            let remaining = depths.get(&type_id).copied().unwrap_or(0);
+            current = information
+                .dependencies
+                .iter()
+                .find(|d| remaining > 0 && depths.get(&d.type_id) == Some(&(remaining - 1)))
+                .map(|d| d.type_id);
+        }
+
+        metrics
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{Dep, ServiceHandler};
+use std::any::Any;
+use std::fmt::{Debug, Formatter};
+use std::ops::Deref;
+
+/// Structs created outside the container, e.g. deserialized jobs or commands,
+/// whose `Injected` fields are filled in afterwards through `ServiceHandler::inject_into`.
+/// Usually derived with `#[derive(Hydrate)]`
+pub trait Hydrate {
+    fn hydrate<H: ServiceHandler>(&mut self, handler: &H);
+}
+
+/// Dependency of a `Hydrate` struct, empty until the struct is hydrated.
+/// Defaults to empty so it can be skipped by deserialization, e.g. with `#[serde(skip)]`
+pub struct Injected<T>(Option<Dep<T>>);
+
+impl<T: Any + Send + Sync> Injected<T> {
+    /// Resolves the service, panicking if it can't be resolved like `get_required_service`
+    pub fn inject<H: ServiceHandler>(&mut self, handler: &H) {
+        self.0 = Some(handler.get_required_service::<T>());
+    }
+}
+
+impl<T> Injected<T> {
+    /// `None` until the struct is hydrated
+    pub fn get(&self) -> Option<&Dep<T>> {
+        self.0.as_ref()
+    }
+}
+
+impl<T> Default for Injected<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T> Clone for Injected<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Debug for Injected<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(_) => write!(f, "Injected({})", std::any::type_name::<T>()),
+            None => write!(f, "Injected(<not hydrated>)"),
+        }
+    }
+}
+
+impl<T> Deref for Injected<T> {
+    type Target = T;
+
+    /// Panics if the struct hasn't been hydrated yet
+    #[track_caller]
+    fn deref(&self) -> &Self::Target {
+        match &self.0 {
+            Some(dep) => dep,
+            None => panic!(
+                "{} was used before its struct was hydrated with inject_into",
+                std::any::type_name::<T>()
+            ),
+        }
+    }
+}
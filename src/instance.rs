@@ -0,0 +1,33 @@
+use crate::factory::factory_information;
+use crate::{Error, ServiceCollection, ServiceCollectionBuilder, ServiceType};
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+impl ServiceCollectionBuilder {
+    /// Registers an already constructed `value` as the singleton `T`, e.g. configuration loaded at startup
+    /// or a client of an external SDK. `build` stores it with the singletons, nothing is initialized.
+    /// It can't be recreated, resolving it after `reload::<T>()` fails.
+    /// A replaced instance is still returned by `get_services`
+    pub fn add_instance<T: Any + Send + Sync>(mut self, value: T) -> Self {
+        let information = factory_information::<T, _>(ServiceType::Singleton, |_| {
+            Err(Error::InitializationFailed {
+                source: "the instance passed to add_instance can't be recreated".into(),
+            })
+        });
+
+        self.register(TypeId::of::<T>(), information);
+        self.last_registered = Some(TypeId::of::<T>());
+        self.instances.push((TypeId::of::<T>(), Arc::new(value)));
+
+        self
+    }
+}
+
+impl ServiceCollection {
+    /// Stores the values passed to `add_instance` with the singletons
+    pub(crate) fn store_instances(&self, instances: Vec<(TypeId, Arc<dyn Any + Send + Sync>)>) {
+        for (type_id, value) in instances {
+            self.singletons.insert(type_id, value);
+        }
+    }
+}
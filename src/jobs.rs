@@ -0,0 +1,127 @@
+use crate::{
+    BoxFuture, Error, Injectable, RetryPolicy, ServiceCollection, ServiceCollectionBuilder,
+    ServiceHandler, ServiceScope,
+};
+use std::any::Any;
+use std::convert::Infallible;
+use std::error::Error as StdError;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::sync::Mutex;
+
+/// Handles jobs of type `J`, registered with `add_job_handler` and resolved in a new scope for every attempt.
+/// The scope is closed with `ServiceScope::close` if the attempt succeeds and dropped if it fails
+pub trait JobHandler<J>: Send + Sync {
+    fn handle<'a>(
+        &'a self,
+        job: &'a J,
+    ) -> BoxFuture<'a, Result<(), Box<dyn StdError + Send + Sync>>>;
+}
+
+trait QueuedJob: Send + Sync {
+    fn run<'a>(&'a self, scope: &'a ServiceScope) -> BoxFuture<'a, Result<(), Error>>;
+}
+
+struct Queued<J>(J);
+
+impl<J: Any + Send + Sync> QueuedJob for Queued<J> {
+    fn run<'a>(&'a self, scope: &'a ServiceScope) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let handler = scope
+                .get_dyn_service::<dyn JobHandler<J>>()
+                .ok_or_else(|| {
+                    Error::not_registered()
+                        .with_type_name(std::any::type_name::<dyn JobHandler<J>>())
+                })?;
+            handler
+                .handle(&self.0)
+                .await
+                .map_err(|source| Error::JobFailed {
+                    job: std::any::type_name::<J>(),
+                    source,
+                })
+        })
+    }
+}
+
+/// In-process queue of jobs, registered as a singleton with `add_job_queue`
+/// and processed by `ServiceCollection::run_jobs`
+pub struct JobQueue {
+    sender: UnboundedSender<Box<dyn QueuedJob>>,
+    receiver: Mutex<UnboundedReceiver<Box<dyn QueuedJob>>>,
+    retry: Option<RetryPolicy>,
+}
+
+impl JobQueue {
+    fn new(retry: Option<RetryPolicy>) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            sender,
+            receiver: Mutex::new(receiver),
+            retry,
+        }
+    }
+
+    /// Stores `job` until a worker hands it to the registered `JobHandler<J>`
+    pub fn enqueue<J: Any + Send + Sync>(&self, job: J) {
+        // The queue holds the receiver itself, so sending can't fail
+        let _ = self.sender.send(Box::new(Queued(job)));
+    }
+
+    async fn process(&self, collection: &ServiceCollection, job: Box<dyn QueuedJob>) {
+        let mut attempt = 1;
+        loop {
+            let scope = collection.create_scope();
+            let result = job.run(&scope).await;
+            // A failed attempt's scope is only dropped, so disposal can't commit its partial work
+            if result.is_ok() {
+                scope.close().await;
+            }
+
+            match (result, &self.retry) {
+                (Ok(()), _) => return,
+                (Err(e), Some(retry)) if retry.should_retry(attempt, &e) => {
+                    tokio::time::sleep(retry.delay(attempt)).await;
+                    attempt += 1;
+                }
+                (Err(_error), _) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(error = %_error, attempt, "Job failed");
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl ServiceCollectionBuilder {
+    /// Registers a `JobQueue` retrying failed jobs according to `retry`, or not at all with `None`
+    pub fn add_job_queue(self, retry: Option<RetryPolicy>) -> Self {
+        self.add_singleton_with(move |_| Ok::<_, Infallible>(JobQueue::new(retry.clone())))
+    }
+
+    /// Registers `H` as the scoped handler of jobs of type `J`
+    pub fn add_job_handler<J, H>(self) -> Self
+    where
+        J: Any + Send + Sync,
+        H: JobHandler<J> + Injectable + Any,
+    {
+        self.add_scoped_as::<dyn JobHandler<J>, H>(|handler| handler)
+    }
+}
+
+impl ServiceCollection {
+    /// Processes jobs of the registered `JobQueue` one at a time until the container's cancellation token
+    /// is triggered, resolving the handler of every attempt in a new scope.
+    /// Spawn it several times to process jobs concurrently
+    pub async fn run_jobs(&self) -> Result<(), Error> {
+        let queue = self.try_get_service::<JobQueue>()?;
+        let token = self.cancellation_token().clone();
+        loop {
+            let next = async { queue.receiver.lock().await.recv().await };
+            match token.run_until_cancelled(next).await {
+                Some(Some(job)) => queue.process(self, job).await,
+                _ => return Ok(()),
+            }
+        }
+    }
+}
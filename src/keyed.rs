@@ -0,0 +1,390 @@
+use crate::factory::{factory_information, into_error};
+use crate::{
+    Error, Memoize, RootScopePolicy, ServiceCollection, ServiceCollectionBuilder, ServiceHandler,
+    ServiceInformation, ServiceProvider, ServiceScope, ServiceType,
+};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Registrations resolved by key with `get_keyed_service` or through `get_services`, in registration order,
+/// with their singletons. Registrations replaced by a later one of the same type are kept without a key
+#[derive(Default)]
+pub(crate) struct KeyedServices {
+    registrations: Vec<(TypeId, Option<&'static str>, ServiceInformation)>,
+    singletons: KeyedCache,
+    /// Transient services memoized with `Memoize::Ttl`
+    memoized: KeyedCache,
+}
+
+/// An instance of a keyed service with the time it was created
+type CachedInstance = (Instant, Arc<dyn Any + Send + Sync>);
+
+/// Instances of keyed services by the index of their registration
+#[derive(Default)]
+pub(crate) struct KeyedCache(Mutex<HashMap<usize, CachedInstance>>);
+
+impl KeyedCache {
+    /// Returns the value stored for `index` unless it's older than `ttl`, otherwise stores a new one.
+    /// The initializer runs without holding the lock, so it can resolve other keyed services.
+    /// If two threads initialize the same service, the first value stored is kept
+    fn get_or_try_insert(
+        &self,
+        index: usize,
+        ttl: Option<Duration>,
+        initialize: impl FnOnce() -> Result<Arc<dyn Any + Send + Sync>, Error>,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let lock = || self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let fresh = |created: &Instant| ttl.is_none_or(|ttl| created.elapsed() < ttl);
+        if let Some((_, value)) = lock().get(&index).filter(|(created, _)| fresh(created)) {
+            return Ok(value.clone());
+        }
+
+        let value = initialize()?;
+        let mut cache = lock();
+        match cache.get(&index) {
+            Some((created, value)) if fresh(created) => Ok(value.clone()),
+            _ => {
+                cache.insert(index, (Instant::now(), value.clone()));
+                Ok(value)
+            }
+        }
+    }
+
+    fn get(&self, index: usize) -> Option<Arc<dyn Any + Send + Sync>> {
+        let cache = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        cache.get(&index).map(|(_, value)| value.clone())
+    }
+
+    /// Every value by the index of its registration
+    fn entries(&self) -> Vec<(usize, CachedInstance)> {
+        let cache = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        cache
+            .iter()
+            .map(|(index, instance)| (*index, instance.clone()))
+            .collect()
+    }
+
+    /// Removes every value, returning them by the index of their registration
+    pub(crate) fn take(&self) -> Vec<(usize, CachedInstance)> {
+        let mut cache = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        cache.drain().collect()
+    }
+
+    /// Discards the values of the given registrations, returns whether one was stored
+    pub(crate) fn remove(&self, indices: impl Iterator<Item = usize>) -> bool {
+        let mut cache = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        indices.fold(false, |removed, index| {
+            cache.remove(&index).is_some() | removed
+        })
+    }
+}
+
+impl KeyedServices {
+    /// `instances` are the values of replaced `add_instance` registrations by their index
+    pub(crate) fn new(
+        registrations: Vec<(TypeId, Option<&'static str>, ServiceInformation)>,
+        instances: Vec<(usize, Arc<dyn Any + Send + Sync>)>,
+    ) -> Self {
+        let now = Instant::now();
+        let instances = instances
+            .into_iter()
+            .map(|(index, value)| (index, (now, value)))
+            .collect();
+        Self {
+            registrations,
+            singletons: KeyedCache(Mutex::new(instances)),
+            memoized: Default::default(),
+        }
+    }
+
+    fn find(&self, type_id: &TypeId, key: &str) -> Result<usize, Error> {
+        self.registrations
+            .iter()
+            .position(|(t, k, _)| t == type_id && *k == Some(key))
+            .ok_or_else(|| Error::UnknownKey(key.to_owned()))
+    }
+
+    /// The registration at `index`
+    pub(crate) fn registration(&self, index: usize) -> Option<&ServiceInformation> {
+        self.registrations
+            .get(index)
+            .map(|(_, _, information)| information)
+    }
+
+    /// The initialized singleton of the registration at `index`
+    pub(crate) fn singleton(&self, index: usize) -> Option<Arc<dyn Any + Send + Sync>> {
+        self.singletons.get(index)
+    }
+
+    /// Every initialized singleton with the time it was created, its type and its registration
+    pub(crate) fn singletons(
+        &self,
+    ) -> Vec<(
+        Instant,
+        TypeId,
+        &ServiceInformation,
+        Arc<dyn Any + Send + Sync>,
+    )> {
+        self.singletons
+            .entries()
+            .into_iter()
+            .filter_map(|(index, (created, value))| {
+                let (type_id, _, information) = self.registrations.get(index)?;
+                Some((created, *type_id, information, value))
+            })
+            .collect()
+    }
+
+    /// Indices of the replaced registrations of `type_id`
+    fn replaced<'a>(&'a self, type_id: &'a TypeId) -> impl Iterator<Item = usize> + 'a {
+        self.registrations
+            .iter()
+            .enumerate()
+            .filter(move |(_, (t, k, _))| t == type_id && k.is_none())
+            .map(|(index, _)| index)
+    }
+
+    /// Indices of every keyed and replaced registration of `type_id`
+    pub(crate) fn of_type<'a>(&'a self, type_id: &'a TypeId) -> impl Iterator<Item = usize> + 'a {
+        self.registrations
+            .iter()
+            .enumerate()
+            .filter(move |(_, (t, _, _))| t == type_id)
+            .map(|(index, _)| index)
+    }
+
+    /// Discards the singletons and memoized values of `type_id`, returns whether one was stored
+    pub(crate) fn reload(&self, type_id: &TypeId) -> bool {
+        self.singletons.remove(self.of_type(type_id)) | self.memoized.remove(self.of_type(type_id))
+    }
+}
+
+/// Drops a registration `get_services` couldn't resolve, logging why
+pub(crate) fn skip_failed(
+    result: Result<Arc<dyn Any + Send + Sync>, Error>,
+    type_name: &'static str,
+) -> Option<Arc<dyn Any + Send + Sync>> {
+    match result {
+        Ok(value) => Some(value),
+        Err(_error) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(error = %_error, service = type_name, "Skipped a registration that couldn't be resolved");
+            #[cfg(not(feature = "tracing"))]
+            let _ = type_name;
+            None
+        }
+    }
+}
+
+impl ServiceCollectionBuilder {
+    /// Registers a service created by a closure under `key`, e.g. one of several pools
+    /// with `add_keyed_service(ServiceType::Singleton, "primary", |_| Pool::connect(primary_url))`.
+    /// Keyed services are resolved with `get_keyed_service` and don't replace the unkeyed registration of `T`,
+    /// registering `T` under the same key again replaces the earlier registration
+    pub fn add_keyed_service<T, E, F>(
+        mut self,
+        type_: ServiceType,
+        key: &'static str,
+        factory: F,
+    ) -> Self
+    where
+        T: Any + Send + Sync,
+        E: Into<Box<dyn StdError + Send + Sync>>,
+        F: Fn(ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+    {
+        let information = factory_information(type_, move |provider| {
+            factory(provider).map_err(|e| into_error(e.into()))
+        });
+
+        let type_id = TypeId::of::<T>();
+        // Replaced in place, so the indices of the other registrations stay the same
+        match self
+            .keyed
+            .iter_mut()
+            .find(|(t, k, _)| *t == type_id && *k == Some(key))
+        {
+            Some(registration) => registration.2 = information,
+            None => self.keyed.push((type_id, Some(key), information)),
+        }
+        // Options like `memoize` only apply to unkeyed registrations
+        self.last_registered = None;
+
+        self
+    }
+
+    pub fn add_keyed_singleton<T, E, F>(self, key: &'static str, factory: F) -> Self
+    where
+        T: Any + Send + Sync,
+        E: Into<Box<dyn StdError + Send + Sync>>,
+        F: Fn(ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+    {
+        self.add_keyed_service(ServiceType::Singleton, key, factory)
+    }
+
+    pub fn add_keyed_scoped<T, E, F>(self, key: &'static str, factory: F) -> Self
+    where
+        T: Any + Send + Sync,
+        E: Into<Box<dyn StdError + Send + Sync>>,
+        F: Fn(ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+    {
+        self.add_keyed_service(ServiceType::Scoped, key, factory)
+    }
+
+    pub fn add_keyed_transient<T, E, F>(self, key: &'static str, factory: F) -> Self
+    where
+        T: Any + Send + Sync,
+        E: Into<Box<dyn StdError + Send + Sync>>,
+        F: Fn(ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+    {
+        self.add_keyed_service(ServiceType::Transient, key, factory)
+    }
+}
+
+impl ServiceCollection {
+    pub(crate) fn resolve_keyed(
+        &self,
+        type_id: &TypeId,
+        key: &str,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        self.resolve_keyed_index(type_id, self.keyed.find(type_id, key)?)
+    }
+
+    /// Every registration of `type_id`, the replaced ones followed by the current one
+    pub(crate) fn resolve_all(
+        &self,
+        type_id: &TypeId,
+    ) -> Vec<Result<Arc<dyn Any + Send + Sync>, Error>> {
+        let mut services: Vec<_> = self
+            .keyed
+            .replaced(type_id)
+            .map(|index| self.resolve_keyed_index(type_id, index))
+            .collect();
+        if self.is_registered(type_id) {
+            services.push(self.try_get_service_by_type_id(type_id));
+        }
+        services
+    }
+
+    /// Resolves a keyed or replaced registration through the middleware, like `try_get_service_by_type_id`
+    pub(crate) fn resolve_keyed_index(
+        &self,
+        type_id: &TypeId,
+        index: usize,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let information = &self.keyed.registrations[index].2;
+        self.resolve_through(type_id, Some(information), &|| {
+            information.check_placement(false)?;
+            self.initialize_keyed(index)
+        })
+    }
+
+    fn initialize_keyed(&self, index: usize) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let (type_id, _, information) = &self.keyed.registrations[index];
+        let initialize = || {
+            let initialize_fn = information
+                .initialize_fn
+                .as_ref()
+                .ok_or(Error::AsyncOnly(information.type_name))?;
+            information.initialize(initialize_fn, self, type_id, Some(index))
+        };
+
+        match (&information.type_, self.root_scope_policy) {
+            (ServiceType::Singleton, _) => self
+                .keyed
+                .singletons
+                .get_or_try_insert(index, None, initialize),
+            (ServiceType::Scoped, RootScopePolicy::ImplicitScope) => {
+                self.root_scope().initialize_keyed(index)
+            }
+            (ServiceType::Scoped, RootScopePolicy::Error) => {
+                Err(Error::ScopedFromRoot(information.type_name))
+            }
+            (ServiceType::Scoped, RootScopePolicy::Transient) | (ServiceType::Transient, _) => {
+                match information.memoize {
+                    Some(Memoize::Ttl(ttl)) => {
+                        self.keyed
+                            .memoized
+                            .get_or_try_insert(index, Some(ttl), initialize)
+                    }
+                    _ => initialize(),
+                }
+            }
+        }
+    }
+}
+
+impl ServiceScope {
+    pub(crate) fn resolve_keyed(
+        &self,
+        type_id: &TypeId,
+        key: &str,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        self.resolve_keyed_index(type_id, self.keyed.find(type_id, key)?)
+    }
+
+    /// Every registration of `type_id`, the replaced ones followed by the current one.
+    /// An override of `type_id` replaces all of them in this scope
+    pub(crate) fn resolve_all(
+        &self,
+        type_id: &TypeId,
+    ) -> Vec<Result<Arc<dyn Any + Send + Sync>, Error>> {
+        if self.overrides.contains_key(type_id) {
+            return vec![self.try_get_service_by_type_id(type_id)];
+        }
+
+        let mut services: Vec<_> = self
+            .keyed
+            .replaced(type_id)
+            .map(|index| self.resolve_keyed_index(type_id, index))
+            .collect();
+        if self.services.snapshot().contains_key(type_id) {
+            services.push(self.try_get_service_by_type_id(type_id));
+        }
+        services
+    }
+
+    /// Resolves a keyed or replaced registration through the middleware, like `try_get_service_by_type_id`
+    fn resolve_keyed_index(
+        &self,
+        type_id: &TypeId,
+        index: usize,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let information = &self.keyed.registrations[index].2;
+        self.resolve_through(type_id, Some(information), &|| {
+            information.check_placement(true)?;
+            self.initialize_keyed(index)
+        })
+    }
+
+    fn initialize_keyed(&self, index: usize) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let (type_id, _, information) = &self.keyed.registrations[index];
+        let initialize_with = |handler: &ServiceScope| {
+            let initialize_fn = information
+                .scoped_initialize_fn
+                .as_ref()
+                .ok_or(Error::AsyncOnly(information.type_name))?;
+            information.initialize(initialize_fn, handler, type_id, Some(index))
+        };
+        // Services shared with other scopes must not see the overrides of this one
+        let initialize_shared = || initialize_with(&self.shared_handler());
+
+        match (&information.type_, &information.memoize) {
+            (ServiceType::Singleton, _) => {
+                self.keyed
+                    .singletons
+                    .get_or_try_insert(index, None, initialize_shared)
+            }
+            (ServiceType::Scoped, _) | (ServiceType::Transient, Some(Memoize::PerScope)) => self
+                .keyed_scoped
+                .get_or_try_insert(index, None, || initialize_with(self)),
+            (ServiceType::Transient, Some(Memoize::Ttl(ttl))) => self
+                .keyed
+                .memoized
+                .get_or_try_insert(index, Some(*ttl), initialize_shared),
+            (ServiceType::Transient, None) => initialize_with(self),
+        }
+    }
+}
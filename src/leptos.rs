@@ -0,0 +1,27 @@
+//! Exposes a `ServiceScope` through Leptos' context system
+
+use crate::{Dep, ServiceHandler, ServiceScope};
+use std::any::Any;
+
+/// Provides the scope to the current component and its children.
+/// Passing a `ServiceCollection` creates a new scope from it
+pub fn provide_scope(scope: impl Into<ServiceScope>) {
+    ::leptos::prelude::provide_context(scope.into());
+}
+
+/// Returns the scope provided by `provide_scope`.
+/// Panics if no scope was provided by a parent component
+pub fn use_scope() -> ServiceScope {
+    ::leptos::prelude::expect_context()
+}
+
+/// Resolves `T` from the provided scope.
+/// Panics if no scope was provided or `T` isn't registered
+pub fn use_service<T: Any + Send + Sync>() -> Dep<T> {
+    ServiceHandler::get_required_service(&use_scope())
+}
+
+/// Resolves `T` from the provided scope, returning `None` if there is no scope or `T` isn't registered
+pub fn try_use_service<T: Any + Send + Sync>() -> Option<Dep<T>> {
+    ServiceHandler::get_service(&::leptos::prelude::use_context::<ServiceScope>()?)
+}
@@ -1,16 +1,167 @@
 use std::any::{Any, TypeId};
-use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::future::Future;
 use std::ops::Deref;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::Duration;
+pub use tokio_util::sync::CancellationToken;
+
+mod asynchronous;
+mod blocking;
+mod build_options;
+mod cache;
+mod clock;
+mod concurrency;
+mod cycle;
+mod data;
+#[cfg(feature = "debug-endpoint")]
+pub mod debug_endpoint;
+mod deprecation;
+#[cfg(feature = "diesel")]
+pub mod diesel;
+mod diff;
+#[cfg(feature = "dioxus")]
+pub mod dioxus;
+mod dispose;
+mod dump_graph;
+mod environment;
+mod error;
+mod factory;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "figment")]
+mod figment;
+mod forward;
+mod from_scope;
+#[cfg(feature = "global")]
+pub mod global;
+mod graph_metrics;
+mod hydrate;
+mod instance;
+mod jobs;
+mod keyed;
+#[cfg(feature = "leptos")]
+pub mod leptos;
+mod memoize;
+mod memory;
+mod middleware;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod observer;
+mod overrides;
+mod placement;
+#[cfg(feature = "profiling")]
+pub mod profiling;
+mod provides;
+mod reload;
+mod retry;
+#[cfg(feature = "rhai")]
+pub mod rhai;
+#[cfg(feature = "rocket")]
+pub mod rocket;
+mod root_scope;
+mod scope_id;
+mod scoped;
+mod secrets;
+#[cfg(feature = "serenity")]
+pub mod serenity;
+#[cfg(feature = "sqlx")]
+pub mod sqlx;
+mod stable_key;
+mod startup_report;
+mod suggest;
+#[cfg(all(feature = "systemd", unix))]
+pub mod systemd;
+#[cfg(feature = "tauri")]
+pub mod tauri;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod trait_object;
+mod type_map;
+mod unit_of_work;
+mod unsync;
+mod usage;
+mod validation;
+mod visit;
+#[cfg(feature = "warp")]
+pub mod warp;
+mod weak_provider;
+
+use arc_swap::ArcSwap;
+use asynchronous::PendingServices;
+pub use asynchronous::{
+    AsyncInitialize, AsyncInitializeFn, AsyncInjectable, AsyncResolveFn, AsyncServiceHandler,
+    BoxFuture, LazyAsyncDep,
+};
+pub use build_options::BuildOptions;
+use cache::ServiceCache;
+pub use clock::{Clock, SystemClock};
+pub use concurrency::ConcurrencyLimit;
+pub use data::RegistrationData;
+pub use diff::{ContainerDiff, InitializerKind, RegistrationSummary};
+pub use dispose::AsyncDispose;
+pub use environment::EnvInitializer;
+pub use error::Error;
+pub use factory::ServiceProvider;
+pub use from_scope::FromScope;
+pub use graph_metrics::{GraphMetrics, ServiceMetrics};
+pub use hydrate::{Hydrate, Injected};
+use indexmap::IndexMap;
+use type_map::TypeMap;
+use usage::Usage;
+
+pub use jobs::{JobHandler, JobQueue};
+pub use memoize::Memoize;
+use memoize::{get_or_memoize, MemoizedServices};
+pub use memory::{MemoryFootprint, MemoryReport, ServiceMemory};
+pub use middleware::{Next, ResolveContext, Resolver};
+pub use observer::Observer;
+use observer::{Observers, ScopeLifecycle};
+pub use overrides::ScopeOverrides;
+pub use provides::Provides;
+pub use retry::{Backoff, RetryPolicy};
+pub use root_scope::RootScopePolicy;
+pub use scope_id::ScopeId;
+pub use secrets::{EnvSecrets, FileSecrets, Secret, SecretKey, SecretsProvider};
+pub use startup_report::StartupReport;
+pub use trait_object::Binding;
+pub use unit_of_work::{Participant, UnitOfWork};
+pub use unsync::Unsync;
+pub use weak_provider::WeakProvider;
 
 pub trait ServiceHandler {
     type ScopeType: ServiceHandler;
     fn get_service_by_type_id(&self, type_id: &TypeId) -> Option<Arc<dyn Any + Send + Sync>>;
 
+    /// Like `get_service_by_type_id`, but reports why the service couldn't be resolved
+    fn try_get_service_by_type_id(
+        &self,
+        type_id: &TypeId,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        self.get_service_by_type_id(type_id)
+            .ok_or_else(Error::not_registered)
+    }
+
     fn create_scope(&self) -> Self::ScopeType
     where
         Self::ScopeType: ServiceHandler;
 
+    /// Resolves the service of type `type_id` registered under `key` with `add_keyed_service`
+    fn try_get_keyed_service_by_type_id(
+        &self,
+        type_id: &TypeId,
+        key: &str,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let _ = type_id;
+        Err(Error::UnknownKey(key.to_owned()))
+    }
+
+    /// Type names of every registered service in registration order,
+    /// used to suggest similar services when resolution fails
+    fn registered_type_names(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
     fn get_service<T: Any + Send + Sync>(&self) -> Option<Dep<T>>
     where
         Self: Sized,
@@ -21,24 +172,289 @@ pub trait ServiceHandler {
             .ok()?))
     }
 
+    /// Resolves `T`, panicking with the reason if it couldn't be resolved.
+    /// Cycles between declared dependencies, e.g. of derived services, are returned by `try_get_service` instead,
+    /// before any of their initializers run
     fn get_required_service<T: Any + Send + Sync>(&self) -> Dep<T>
     where
         Self: Sized,
     {
-        self.get_service::<T>().unwrap()
+        match self.try_get_service::<T>() {
+            Ok(service) => service,
+            Err(error) => panic!(
+                "{}",
+                suggest::failed_service_message(
+                    std::any::type_name::<T>(),
+                    &error,
+                    self.registered_type_names()
+                )
+            ),
+        }
+    }
+
+    /// Resolves `T`, returning the error of a failed initializer instead of discarding it
+    fn try_get_service<T: Any + Send + Sync>(&self) -> Result<Dep<T>, Error>
+    where
+        Self: Sized,
+    {
+        let value = self
+            .try_get_service_by_type_id(&TypeId::of::<T>())
+            .map_err(|e| e.with_type_name(std::any::type_name::<T>()))?;
+        Ok(Dep(value.downcast::<T>().map_err(|_| {
+            Error::TypeMismatch(std::any::type_name::<T>())
+        })?))
+    }
+
+    /// Resolves every registration of `type_id` in registration order,
+    /// including the ones replaced by a later registration
+    fn get_services_by_type_id(
+        &self,
+        type_id: &TypeId,
+    ) -> Vec<Result<Arc<dyn Any + Send + Sync>, Error>> {
+        match self.try_get_service_by_type_id(type_id) {
+            Err(Error::NotRegistered {
+                requested_by: None, ..
+            }) => Vec::new(),
+            result => vec![result],
+        }
+    }
+
+    /// Resolves the `T` registered under `key` with `add_keyed_service` or one of its shorthands
+    fn get_keyed_service<T: Any + Send + Sync>(&self, key: &str) -> Option<Dep<T>>
+    where
+        Self: Sized,
+    {
+        self.try_get_keyed_service(key).ok()
+    }
+
+    /// Like `get_keyed_service`, but reports why the service couldn't be resolved
+    fn try_get_keyed_service<T: Any + Send + Sync>(&self, key: &str) -> Result<Dep<T>, Error>
+    where
+        Self: Sized,
+    {
+        let value = self.try_get_keyed_service_by_type_id(&TypeId::of::<T>(), key)?;
+        Ok(Dep(value.downcast::<T>().map_err(|_| {
+            Error::TypeMismatch(std::any::type_name::<T>())
+        })?))
+    }
+
+    /// Resolves every registration of `T` in registration order, e.g. plugins registered one after another.
+    /// `get_service` only resolves the last one
+    fn try_get_services<T: Any + Send + Sync>(&self) -> Result<Vec<Dep<T>>, Error>
+    where
+        Self: Sized,
+    {
+        self.get_services_by_type_id(&TypeId::of::<T>())
+            .into_iter()
+            .map(|value| {
+                value?
+                    .downcast::<T>()
+                    .map(Dep)
+                    .map_err(|_| Error::TypeMismatch(std::any::type_name::<T>()))
+            })
+            .collect()
+    }
+
+    /// Like `try_get_services`, but skips the registrations that couldn't be resolved,
+    /// logging why with the `tracing` feature
+    fn get_services<T: Any + Send + Sync>(&self) -> Vec<Dep<T>>
+    where
+        Self: Sized,
+    {
+        self.get_services_by_type_id(&TypeId::of::<T>())
+            .into_iter()
+            .filter_map(|value| keyed::skip_failed(value, std::any::type_name::<T>()))
+            .filter_map(|value| value.downcast::<T>().ok().map(Dep))
+            .collect()
+    }
+
+    /// Resolves every registration of the trait object `T` in registration order,
+    /// e.g. `get_dyn_services::<dyn Plugin>()` after several `add_singleton_as::<dyn Plugin, _>`.
+    /// Skips the registrations that couldn't be resolved like `get_services`
+    fn get_dyn_services<T: ?Sized + Send + Sync + 'static>(&self) -> Vec<Dep<T>>
+    where
+        Self: Sized,
+    {
+        self.get_services_by_type_id(&TypeId::of::<T>())
+            .into_iter()
+            .filter_map(|value| keyed::skip_failed(value, std::any::type_name::<T>()))
+            .filter_map(|value| {
+                Some(Dep(value
+                    .downcast_ref::<trait_object::TraitObject<T>>()?
+                    .object
+                    .clone()))
+            })
+            .collect()
+    }
+
+    /// Resolves a trait object registered through `add_service_as` or one of its shorthands
+    fn get_dyn_service<T: ?Sized + Send + Sync + 'static>(&self) -> Option<Dep<T>>
+    where
+        Self: Sized,
+    {
+        let value = self.get_service_by_type_id(&TypeId::of::<T>())?;
+        Some(Dep(value
+            .downcast_ref::<trait_object::TraitObject<T>>()?
+            .object
+            .clone()))
+    }
+
+    fn get_required_dyn_service<T: ?Sized + Send + Sync + 'static>(&self) -> Dep<T>
+    where
+        Self: Sized,
+    {
+        let value = match self.try_get_service_by_type_id(&TypeId::of::<T>()) {
+            Ok(value) => value,
+            Err(error) => panic!(
+                "{}",
+                suggest::failed_service_message(
+                    std::any::type_name::<T>(),
+                    &error,
+                    self.registered_type_names()
+                )
+            ),
+        };
+        match value.downcast_ref::<trait_object::TraitObject<T>>() {
+            Some(object) => Dep(object.object.clone()),
+            None => panic!("{}", Error::TypeMismatch(std::any::type_name::<T>())),
+        }
+    }
+
+    /// Resolves `T` if it's registered, otherwise falls back to its `Default` value.
+    /// The fallback isn't stored in the container
+    fn get_service_or_default<T: Any + Send + Sync + Default>(&self) -> Dep<T>
+    where
+        Self: Sized,
+    {
+        self.get_service::<T>()
+            .unwrap_or_else(|| Dep(Arc::new(T::default())))
+    }
+
+    /// Constructs `T` directly from the container without it being registered,
+    /// e.g. a tuple of dependencies like `(Dep<A>, Dep<B>)`
+    fn resolve<T: Injectable>(&self) -> T
+    where
+        Self: Sized,
+    {
+        T::inject(self)
+    }
+
+    /// Fills in the `Injected` fields of a struct that was created outside the container,
+    /// e.g. by deserialization
+    fn inject_into<T: Hydrate>(&self, value: &mut T)
+    where
+        Self: Sized,
+    {
+        value.hydrate(self);
+    }
+
+    /// Owned handle used by `LazyAsyncDep` to resolve services after injection.
+    /// `None` for handlers that can't resolve services asynchronously
+    fn async_resolver(&self) -> Option<AsyncResolveFn> {
+        None
     }
 }
 
 pub trait Injectable {
     fn inject<T: ServiceHandler>(handler: &T) -> Self;
+
+    /// Services resolved by `inject`, emitted by the derive macro
+    fn dependencies() -> Vec<Dependency>
+    where
+        Self: Sized,
+    {
+        Vec::new()
+    }
+
+    /// Trait objects that are registered alongside the service by `add_singleton`, `add_scoped`
+    /// and `add_transient`, emitted by the derive macro's `implements` attribute
+    fn bindings() -> Vec<Binding>
+    where
+        Self: Sized,
+    {
+        Vec::new()
+    }
+}
+
+/// Marker for services that are meant to come from the container, emitted by the derive macro.
+/// Lets generic code state that requirement, e.g. `T: Resolvable + Any + Send + Sync`
+pub trait Resolvable {}
+
+/// A service another service depends on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Dependency {
+    pub type_id: TypeId,
+    pub type_name: &'static str,
+}
+
+impl Dependency {
+    pub fn of<T: ?Sized + 'static>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            type_name: std::any::type_name::<T>(),
+        }
+    }
+}
+
+/// Marker for types that are injected through their `Default` implementation
+#[cfg(feature = "default-injectable")]
+pub trait InjectDefault: Default {}
+
+#[cfg(feature = "default-injectable")]
+impl<I: InjectDefault> Injectable for I {
+    fn inject<T: ServiceHandler>(_handler: &T) -> Self {
+        I::default()
+    }
 }
 
+#[cfg(feature = "default-injectable")]
+impl<I: InjectDefault> Resolvable for I {}
+
 /// Trait for initializing structs not owned by you.
 /// Prefer `Injectable` when able to as it's less messy
 pub trait Initialize<R: Any + Send + Sync> {
     fn initialize<T: ServiceHandler>(&self, handler: &T) -> R;
+
+    /// Called by the container with the payload attached to the registration through `with_data`.
+    /// Override it to configure the service from the payload, by default the payload is ignored
+    fn initialize_with_data<T: ServiceHandler>(&self, handler: &T, _data: &RegistrationData) -> R {
+        self.initialize(handler)
+    }
+}
+
+impl<D: Any + Send + Sync> Injectable for Dep<D> {
+    fn inject<T: ServiceHandler>(handler: &T) -> Self {
+        handler.get_required_service()
+    }
+
+    fn dependencies() -> Vec<Dependency> {
+        vec![Dependency::of::<D>()]
+    }
 }
 
+macro_rules! impl_injectable_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Injectable),+> Injectable for ($($name,)+) {
+            fn inject<T: ServiceHandler>(handler: &T) -> Self {
+                ($($name::inject(handler),)+)
+            }
+
+            fn dependencies() -> Vec<Dependency> {
+                [$($name::dependencies()),+].concat()
+            }
+        }
+    };
+}
+
+impl_injectable_tuple!(A);
+impl_injectable_tuple!(A, B);
+impl_injectable_tuple!(A, B, C);
+impl_injectable_tuple!(A, B, C, D);
+impl_injectable_tuple!(A, B, C, D, E);
+impl_injectable_tuple!(A, B, C, D, E, F);
+impl_injectable_tuple!(A, B, C, D, E, F, G);
+impl_injectable_tuple!(A, B, C, D, E, F, G, H);
+
 #[derive(Clone)]
 struct DefaultInitializer;
 
@@ -48,7 +464,7 @@ impl<I: Injectable + Any + Send + Sync> Initialize<I> for DefaultInitializer {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ServiceType {
     Singleton,
     Scoped,
@@ -56,9 +472,35 @@ pub enum ServiceType {
 }
 
 /// Used mainly by derive macro ``Injectable`` to identify what is considered a service and what is considered non-service
-pub struct Dep<T>(Arc<T>);
+pub struct Dep<T: ?Sized>(Arc<T>);
+
+impl<T: ?Sized> Dep<T> {
+    /// Whether both point to the same instance, e.g. the same singleton
+    pub fn ptr_eq(&self, other: &Dep<T>) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Number of handles to this instance, including the container's own if it caches the service
+    pub fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.0)
+    }
+}
+
+impl<T> Dep<T> {
+    /// Takes the value out if this is the only handle to it, e.g. a transient nothing else holds.
+    /// Returns the handle unchanged otherwise, e.g. for a singleton the container caches
+    pub fn try_into_inner(self) -> Result<T, Self> {
+        Arc::try_unwrap(self.0).map_err(Self)
+    }
+}
+
+impl<T: ?Sized> Clone for Dep<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
 
-impl<T> Deref for Dep<T> {
+impl<T: ?Sized> Deref for Dep<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -66,42 +508,287 @@ impl<T> Deref for Dep<T> {
     }
 }
 
-pub type InitializeFn<T> = Arc<dyn Fn(&T) -> Box<dyn Any + Send + Sync>>;
+impl<T: ?Sized> AsRef<T> for Dep<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for Dep<T> {
+    fn borrow(&self) -> &T {
+        &self.0
+    }
+}
+
+pub type InitializeFn<T> =
+    Arc<dyn Fn(&T, &RegistrationData) -> Result<Box<dyn Any + Send + Sync>, Error> + Send + Sync>;
 
 #[derive(Clone)]
 struct ServiceInformation {
-    pub(crate) initialize_fn: InitializeFn<ServiceCollection>,
+    pub(crate) initialize_fn: Option<InitializeFn<ServiceCollection>>,
+    pub(crate) scoped_initialize_fn: Option<InitializeFn<ServiceScope>>,
+    pub(crate) initialize_async_fn: Option<AsyncInitializeFn<ServiceCollection>>,
+    pub(crate) scoped_initialize_async_fn: Option<AsyncInitializeFn<ServiceScope>>,
     pub(crate) type_: ServiceType,
+    pub(crate) type_name: &'static str,
+    pub(crate) memoize: Option<Memoize>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) data: RegistrationData,
+    /// Whether `data` is handed to the initializer, which closures and async initializers don't take
+    pub(crate) receives_data: bool,
+    /// Declared through `Injectable::dependencies`, empty for custom initializers
+    pub(crate) dependencies: Vec<Dependency>,
+    /// Concrete service behind a trait object registration
+    pub(crate) implementation: Option<trait_object::Implementation>,
+    /// Migration hint logged the first time the service is resolved
+    pub(crate) deprecated: Option<&'static str>,
+    pub(crate) memory_footprint: Option<memory::FootprintFn>,
+    pub(crate) async_dispose: Option<dispose::AsyncDisposeFn>,
+    pub(crate) placement: placement::Placement,
+    /// Limits how many async initializers of a group run at once
+    pub(crate) concurrency: Option<concurrency::ConcurrencyLimit>,
+    /// Runs the synchronous initializer on the blocking thread pool when resolved asynchronously
+    pub(crate) blocking: bool,
+    /// Key identifying the service independently of its `TypeId`, assigned with `stable_key`
+    pub(crate) stable_key: Option<&'static str>,
 }
 
+impl ServiceInformation {
+    fn new<T: Any + Send + Sync, I: Initialize<T> + Clone + Send + Sync + 'static>(
+        type_: ServiceType,
+        initializer: I,
+    ) -> Self {
+        let closure_clone = initializer.clone();
+        let collection_closure: InitializeFn<ServiceCollection> =
+            Arc::new(move |x, data| Ok(Box::new(closure_clone.initialize_with_data(x, data))));
+        let scoped_closure: InitializeFn<ServiceScope> =
+            Arc::new(move |x, data| Ok(Box::new(initializer.initialize_with_data(x, data))));
+
+        Self {
+            initialize_fn: Some(collection_closure),
+            scoped_initialize_fn: Some(scoped_closure),
+            initialize_async_fn: None,
+            scoped_initialize_async_fn: None,
+            type_,
+            type_name: std::any::type_name::<T>(),
+            memoize: None,
+            timeout: None,
+            retry: None,
+            data: RegistrationData::default(),
+            receives_data: true,
+            dependencies: Vec::new(),
+            implementation: None,
+            deprecated: None,
+            memory_footprint: None,
+            async_dispose: None,
+            placement: Default::default(),
+            concurrency: None,
+            blocking: false,
+            stable_key: None,
+        }
+    }
+}
+
+impl ServiceInformation {
+    /// Runs the initializer of the registration of `type_id`,
+    /// `index` is the index of keyed and replaced registrations
+    fn initialize<H: cycle::Initializing>(
+        &self,
+        initialize_fn: &InitializeFn<H>,
+        handler: &H,
+        type_id: &TypeId,
+        index: Option<usize>,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let _initializing = cycle::enter(self, handler, *type_id, index)?;
+        #[cfg(feature = "profiling")]
+        let started = std::time::Instant::now();
+        let value = initialize_fn(handler, &self.data)
+            .map(Arc::from)
+            .map_err(|e| e.requested_by(self.type_name));
+        #[cfg(feature = "profiling")]
+        profiling::record_initialization(self.type_name, started);
+
+        value
+    }
+}
+
+/// Registrations of a container, shared between the collection and its scopes, in registration order.
+/// Registering after build swaps in an extended copy so reading never takes a lock
 #[derive(Clone)]
-struct ScopedServiceInformation {
-    initialize_fn: InitializeFn<ServiceScope>,
-    type_: ServiceType,
+struct Registry {
+    services: Arc<ArcSwap<TypeMap<ServiceInformation>>>,
+    /// Services whose declared dependencies lead into a cycle, with the cycle
+    cycles: Arc<ArcSwap<TypeMap<Vec<&'static str>>>>,
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl Registry {
+    fn new(services: IndexMap<TypeId, ServiceInformation>) -> Self {
+        let services: TypeMap<_> = services.into_iter().collect();
+        Self {
+            cycles: Arc::new(ArcSwap::from_pointee(cycle::declared_cycles(&services))),
+            services: Arc::new(ArcSwap::from_pointee(services)),
+            write_lock: Default::default(),
+        }
+    }
+
+    fn snapshot(&self) -> Arc<TypeMap<ServiceInformation>> {
+        self.services.load_full()
+    }
+
+    /// The cycle the declared dependencies of `type_id` lead into, if any
+    fn declared_cycle(&self, type_id: &TypeId) -> Option<Vec<&'static str>> {
+        self.cycles.load().get(type_id).cloned()
+    }
+
+    fn insert_if_absent(&self, type_id: TypeId, information: ServiceInformation) {
+        let _guard = self
+            .write_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let current = self.services.load();
+        if !current.contains_key(&type_id) {
+            let mut services = TypeMap::clone(&current);
+            services.insert(type_id, information);
+            self.cycles
+                .store(Arc::new(cycle::declared_cycles(&services)));
+            self.services.store(Arc::new(services));
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct ServiceCollection {
-    service_info: Arc<HashMap<TypeId, ServiceInformation>>,
-    scoped_service_info: Arc<HashMap<TypeId, ScopedServiceInformation>>,
-    singletons: Arc<RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>>,
+    service_info: Registry,
+    singletons: Arc<ServiceCache>,
+    pending_singletons: PendingServices,
+    memoized: MemoizedServices,
+    resolvers: Arc<Vec<Resolver>>,
+    observers: Observers,
+    usage: Arc<Usage>,
+    root_scope_policy: RootScopePolicy,
+    root_scope: Arc<OnceLock<ServiceScope>>,
+    cancellation_token: CancellationToken,
+    keyed: Arc<keyed::KeyedServices>,
 }
 
 impl ServiceCollection {
-    fn get_singleton(&self, type_id: &TypeId) -> Option<Arc<dyn Any + Send + Sync>> {
-        let value = {
-            let read = self.singletons.read().ok()?;
-            read.get(type_id).cloned()
-        };
+    /// Token observed by every async resolution of this container and its scopes.
+    /// Cancelling it aborts pending initializations with `Error::Cancelled`
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
 
-        if let Some(v) = value {
-            Some(v)
+    fn get_singleton(
+        &self,
+        information: &ServiceInformation,
+        type_id: &TypeId,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        if let Some(v) = self.singletons.get(type_id) {
+            Ok(v)
         } else {
-            let information = self.service_info.get(type_id)?;
-            let value: Arc<dyn Any + Send + Sync> = (information.initialize_fn)(self).into();
-            let mut write = self.singletons.write().ok()?;
-            write.insert(*type_id, value.clone());
-            Some(value)
+            let initialize_fn = information
+                .initialize_fn
+                .as_ref()
+                .ok_or(Error::AsyncOnly(information.type_name))?;
+            let value = information.initialize(initialize_fn, self, type_id, None)?;
+            self.singletons.insert(*type_id, value.clone());
+
+            for observer in self.observers.iter() {
+                observer.on_singleton_created(type_id);
+            }
+            Ok(value)
+        }
+    }
+
+    /// Resolves `T`, registering it with the given initializer first if it isn't registered yet.
+    /// The registration is visible to every scope of this collection
+    pub fn get_or_register<T, I>(&self, type_: ServiceType, initializer: I) -> Option<Dep<T>>
+    where
+        T: Any + Send + Sync,
+        I: Initialize<T> + Clone + Send + Sync + 'static,
+    {
+        self.service_info.insert_if_absent(
+            TypeId::of::<T>(),
+            ServiceInformation::new::<T, I>(type_, initializer),
+        );
+        self.get_service::<T>()
+    }
+
+    pub fn is_registered(&self, type_id: &TypeId) -> bool {
+        self.service_info.snapshot().contains_key(type_id)
+    }
+
+    /// Runs `f` in a new scope that is dropped once `f` returns, returning what `f` returns.
+    /// The scope outlives `f` only if `f` keeps a clone of it
+    pub fn run_scoped<R>(&self, f: impl FnOnce(&ServiceScope) -> R) -> R {
+        let scope = ServiceScope::create(self);
+        f(&scope)
+    }
+
+    /// Awaits the future returned by `f` with a new scope that is closed once the future completes,
+    /// disposing its scoped services like `ServiceScope::close`
+    pub async fn run_scoped_async<R, F>(&self, f: impl FnOnce(ServiceScope) -> F) -> R
+    where
+        F: Future<Output = R>,
+    {
+        let scope = ServiceScope::create(self);
+        let result = f(scope.clone()).await;
+        scope.close().await;
+        result
+    }
+
+    /// Runs `resolve` through the resolver middleware and reports the resolution to observers and usage tracking
+    fn resolve_through(
+        &self,
+        type_id: &TypeId,
+        information: Option<&ServiceInformation>,
+        resolve: &dyn Fn() -> Result<Arc<dyn Any + Send + Sync>, Error>,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let context = ResolveContext {
+            type_id,
+            type_name: information.map(|i| i.type_name),
+            service_type: information.map(|i| i.type_.clone()),
+        };
+
+        let value = Next::new(&self.resolvers, &context, resolve).run()?;
+        for observer in self.observers.iter() {
+            observer.on_resolved(type_id);
+        }
+        self.usage.mark(type_id);
+        Ok(value)
+    }
+
+    fn resolve(&self, type_id: &TypeId) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let services = self.service_info.snapshot();
+        let Some(info) = services.get(type_id) else {
+            if *type_id == TypeId::of::<WeakProvider>() {
+                return Ok(weak_provider::service(self));
+            }
+            return clock::default_service(type_id).ok_or_else(Error::not_registered);
+        };
+        info.check_placement(false)?;
+        match (&info.type_, self.root_scope_policy) {
+            (ServiceType::Singleton, _) => return self.get_singleton(info, type_id),
+            (ServiceType::Scoped, RootScopePolicy::ImplicitScope) => {
+                return self.root_scope().resolve(type_id, info);
+            }
+            (ServiceType::Scoped, RootScopePolicy::Error) => {
+                return Err(Error::ScopedFromRoot(info.type_name));
+            }
+            _ => {}
+        }
+
+        let initialize_fn = info
+            .initialize_fn
+            .as_ref()
+            .ok_or(Error::AsyncOnly(info.type_name))?;
+        match info.memoize {
+            Some(Memoize::Ttl(ttl)) => get_or_memoize(&self.memoized, type_id, ttl, || {
+                info.initialize(initialize_fn, self, type_id, None)
+            }),
+            _ => info.initialize(initialize_fn, self, type_id, None),
         }
     }
 }
@@ -110,16 +797,15 @@ impl ServiceHandler for ServiceCollection {
     type ScopeType = ServiceScope;
 
     fn get_service_by_type_id(&self, type_id: &TypeId) -> Option<Arc<dyn Any + Send + Sync>> {
-        let information = self.service_info.get(type_id);
+        self.try_get_service_by_type_id(type_id).ok()
+    }
 
-        if let Some(info) = information {
-            match info.type_ {
-                ServiceType::Singleton => Some(self.get_singleton(type_id)?),
-                _ => Some((info.initialize_fn)(self).into()),
-            }
-        } else {
-            None
-        }
+    fn try_get_service_by_type_id(
+        &self,
+        type_id: &TypeId,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let services = self.service_info.snapshot();
+        self.resolve_through(type_id, services.get(type_id), &|| self.resolve(type_id))
     }
 
     fn create_scope(&self) -> Self::ScopeType
@@ -128,54 +814,154 @@ impl ServiceHandler for ServiceCollection {
     {
         Self::ScopeType::create(self)
     }
+
+    fn try_get_keyed_service_by_type_id(
+        &self,
+        type_id: &TypeId,
+        key: &str,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        self.resolve_keyed(type_id, key)
+    }
+
+    fn get_services_by_type_id(
+        &self,
+        type_id: &TypeId,
+    ) -> Vec<Result<Arc<dyn Any + Send + Sync>, Error>> {
+        self.resolve_all(type_id)
+    }
+
+    fn registered_type_names(&self) -> Vec<&'static str> {
+        let services = self.service_info.snapshot();
+        services.iter().map(|(_, i)| i.type_name).collect()
+    }
+
+    fn async_resolver(&self) -> Option<AsyncResolveFn> {
+        Some(asynchronous::async_resolver(self))
+    }
 }
 
 #[derive(Clone)]
 pub struct ServiceScope {
-    services: Arc<HashMap<TypeId, ScopedServiceInformation>>,
-    singletons: Arc<RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>>,
-    scoped: Arc<RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>>,
+    services: Registry,
+    singletons: Arc<ServiceCache>,
+    scoped: Arc<ServiceCache>,
+    pending_singletons: PendingServices,
+    pending_scoped: PendingServices,
+    memoized: MemoizedServices,
+    resolvers: Arc<Vec<Resolver>>,
+    lifecycle: Arc<ScopeLifecycle>,
+    usage: Arc<Usage>,
+    cancellation_token: CancellationToken,
+    overrides: overrides::Overrides,
+    keyed: Arc<keyed::KeyedServices>,
+    keyed_scoped: Arc<keyed::KeyedCache>,
 }
 
 impl ServiceScope {
-    fn get_service(
+    fn resolve(
         &self,
         type_id: &TypeId,
-        type_: ServiceType,
-    ) -> Option<Arc<dyn Any + Send + Sync>> {
-        let value = match type_ {
-            ServiceType::Singleton => self.singletons.read().ok()?.get(type_id).cloned(),
-            ServiceType::Scoped => self.scoped.read().ok()?.get(type_id).cloned(),
-            ServiceType::Transient => {
-                return Some((self.services.get(type_id)?.initialize_fn)(self).into())
+        information: &ServiceInformation,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let initialize_with = |handler: &ServiceScope| {
+            let initialize_fn = information
+                .scoped_initialize_fn
+                .as_ref()
+                .ok_or(Error::AsyncOnly(information.type_name))?;
+            information.initialize(initialize_fn, handler, type_id, None)
+        };
+        let initialize = || initialize_with(self);
+        // Services shared with other scopes must not see the overrides of this one
+        let initialize_shared = || initialize_with(&self.shared_handler());
+
+        match (&information.type_, &information.memoize) {
+            (ServiceType::Singleton, _) => {
+                if let Some(v) = self.singletons.get(type_id) {
+                    return Ok(v);
+                }
+
+                let value = initialize_shared()?;
+                self.singletons.insert(*type_id, value.clone());
+                for observer in self.lifecycle.observers.iter() {
+                    observer.on_singleton_created(type_id);
+                }
+                Ok(value)
+            }
+            (ServiceType::Scoped, _) | (ServiceType::Transient, Some(Memoize::PerScope)) => {
+                if let Some(v) = self.scoped.get(type_id) {
+                    return Ok(v);
+                }
+
+                let value = initialize()?;
+                self.scoped.insert(*type_id, value.clone());
+                Ok(value)
+            }
+            (ServiceType::Transient, Some(Memoize::Ttl(ttl))) => {
+                get_or_memoize(&self.memoized, type_id, *ttl, initialize_shared)
             }
+            (ServiceType::Transient, None) => initialize(),
+        }
+    }
+
+    /// Runs `resolve` through the resolver middleware and reports the resolution to observers and usage tracking
+    fn resolve_through(
+        &self,
+        type_id: &TypeId,
+        information: Option<&ServiceInformation>,
+        resolve: &dyn Fn() -> Result<Arc<dyn Any + Send + Sync>, Error>,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let context = ResolveContext {
+            type_id,
+            type_name: information.map(|i| i.type_name),
+            service_type: information.map(|i| i.type_.clone()),
         };
 
-        if let Some(v) = value {
-            Some(v)
-        } else {
-            let information = self.services.get(type_id)?;
-            let value: Arc<dyn Any + Send + Sync> = (information.initialize_fn)(self).into();
-
-            match type_ {
-                ServiceType::Singleton => self
-                    .singletons
-                    .write()
-                    .ok()?
-                    .insert(*type_id, value.clone()),
-                ServiceType::Scoped => self.scoped.write().ok()?.insert(*type_id, value.clone()),
-                ServiceType::Transient => panic!(),
-            };
-
-            Some(value)
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "resolve",
+            scope_id = %self.id(),
+            service = context.type_name,
+        )
+        .entered();
+
+        let value = Next::new(&self.resolvers, &context, resolve).run()?;
+        for observer in self.lifecycle.observers.iter() {
+            observer.on_resolved(type_id);
         }
+        self.usage.mark(type_id);
+        Ok(value)
+    }
+
+    /// Correlation id of this scope, also resolvable as a `ScopeId` service
+    pub fn id(&self) -> ScopeId {
+        self.lifecycle.scope_id()
+    }
+
+    /// Cancelled once the last handle to this scope is dropped, or when the collection's token is cancelled.
+    /// Also resolvable as a `CancellationToken` service, so scoped services can stop their work with the scope
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.lifecycle.cancellation_token
     }
 
     pub fn create(handler: &ServiceCollection) -> Self {
         Self {
-            services: handler.scoped_service_info.clone(),
+            services: handler.service_info.clone(),
             singletons: handler.singletons.clone(),
             scoped: Arc::new(Default::default()),
+            pending_singletons: handler.pending_singletons.clone(),
+            pending_scoped: Arc::new(Default::default()),
+            memoized: handler.memoized.clone(),
+            resolvers: handler.resolvers.clone(),
+            lifecycle: Arc::new(ScopeLifecycle::new(
+                handler.observers.clone(),
+                &handler.cancellation_token,
+                weak_provider::service(handler),
+            )),
+            usage: handler.usage.clone(),
+            cancellation_token: handler.cancellation_token.clone(),
+            overrides: Default::default(),
+            keyed: handler.keyed.clone(),
+            keyed_scoped: Arc::new(Default::default()),
         }
     }
 }
@@ -184,13 +970,30 @@ impl ServiceHandler for ServiceScope {
     type ScopeType = Self;
 
     fn get_service_by_type_id(&self, type_id: &TypeId) -> Option<Arc<dyn Any + Send + Sync>> {
-        let information = self.services.get(type_id);
+        self.try_get_service_by_type_id(type_id).ok()
+    }
 
-        if let Some(info) = information {
-            self.get_service(type_id, info.type_.clone())
-        } else {
-            None
-        }
+    fn try_get_service_by_type_id(
+        &self,
+        type_id: &TypeId,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let services = self.services.snapshot();
+        let information = services.get(type_id);
+        self.resolve_through(type_id, information, &|| match information {
+            _ if self.overrides.contains_key(type_id) => Ok(self.overrides[type_id].clone()),
+            Some(information) => {
+                information.check_placement(true)?;
+                self.resolve(type_id, information)
+            }
+            None if *type_id == TypeId::of::<ScopeId>() => Ok(self.lifecycle.id_service.clone()),
+            None if *type_id == TypeId::of::<CancellationToken>() => {
+                Ok(self.lifecycle.cancellation_service.clone())
+            }
+            None if *type_id == TypeId::of::<WeakProvider>() => {
+                Ok(self.lifecycle.root_service.clone())
+            }
+            None => clock::default_service(type_id).ok_or_else(Error::not_registered),
+        })
     }
 
     fn create_scope(&self) -> Self::ScopeType
@@ -199,73 +1002,195 @@ impl ServiceHandler for ServiceScope {
     {
         self.clone()
     }
+
+    fn try_get_keyed_service_by_type_id(
+        &self,
+        type_id: &TypeId,
+        key: &str,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        self.resolve_keyed(type_id, key)
+    }
+
+    fn get_services_by_type_id(
+        &self,
+        type_id: &TypeId,
+    ) -> Vec<Result<Arc<dyn Any + Send + Sync>, Error>> {
+        self.resolve_all(type_id)
+    }
+
+    fn registered_type_names(&self) -> Vec<&'static str> {
+        let services = self.services.snapshot();
+        services.iter().map(|(_, i)| i.type_name).collect()
+    }
+
+    fn async_resolver(&self) -> Option<AsyncResolveFn> {
+        Some(asynchronous::async_resolver(self))
+    }
 }
 
 impl From<ServiceCollection> for ServiceScope {
     fn from(value: ServiceCollection) -> Self {
+        let root_service = weak_provider::service(&value);
         Self {
-            services: value.scoped_service_info,
+            services: value.service_info,
             singletons: value.singletons,
             scoped: Arc::new(Default::default()),
+            pending_singletons: value.pending_singletons,
+            pending_scoped: Arc::new(Default::default()),
+            memoized: value.memoized,
+            resolvers: value.resolvers,
+            lifecycle: Arc::new(ScopeLifecycle::new(
+                value.observers,
+                &value.cancellation_token,
+                root_service,
+            )),
+            usage: value.usage,
+            cancellation_token: value.cancellation_token,
+            overrides: Default::default(),
+            keyed: value.keyed,
+            keyed_scoped: Arc::new(Default::default()),
         }
     }
 }
 
 #[derive(Default, Clone)]
 pub struct ServiceCollectionBuilder {
-    services: HashMap<TypeId, ServiceInformation>,
-    scoped_services: HashMap<TypeId, ScopedServiceInformation>,
+    services: IndexMap<TypeId, ServiceInformation>,
+    last_registered: Option<TypeId>,
+    default_timeout: Option<Duration>,
+    resolvers: Vec<Resolver>,
+    observers: Vec<Arc<dyn Observer>>,
+    cancellation_token: Option<CancellationToken>,
+    /// Registrations made with `add_*_per_env`
+    per_env: IndexMap<TypeId, environment::PerEnv>,
+    /// Environment set with `env`, picking the `per_env` registrations
+    env: Option<String>,
+    warn_unused_on_drop: bool,
+    root_scope_policy: RootScopePolicy,
+    /// Type names of registrations that replaced an earlier one, for `BuildOptions::fail_on_duplicates`
+    duplicates: Vec<&'static str>,
+    /// Registrations made with `add_keyed_service` and replaced registrations, as `(type, key, registration)`
+    keyed: Vec<(TypeId, Option<&'static str>, ServiceInformation)>,
+    /// Services registered with `add_instance` that weren't replaced afterwards
+    instances: Vec<(TypeId, Arc<dyn Any + Send + Sync>)>,
+    /// Services registered with `add_instance` and replaced afterwards, by their index in `keyed`
+    keyed_instances: Vec<(usize, Arc<dyn Any + Send + Sync>)>,
+    /// Configuration sections bound with `bind_section`, as `(key, type)`
+    #[cfg(feature = "figment")]
+    sections: Vec<(&'static str, TypeId)>,
 }
 
 impl ServiceCollectionBuilder {
-    pub fn add_service<T: Any + Send + Sync, I: Initialize<T> + Clone + 'static>(
+    pub fn add_service<T: Any + Send + Sync, I: Initialize<T> + Clone + Send + Sync + 'static>(
         mut self,
         type_: ServiceType,
         initializer: I,
-    ) -> Self
-    {
-        let closure_clone = initializer.clone();
-        let collection_closure: InitializeFn<ServiceCollection> =
-            Arc::new(move |x| Box::new(closure_clone.initialize(x) ));
-        let scoped_closure: InitializeFn<ServiceScope> =
-            Arc::new(move |x| Box::new(initializer.initialize(x)));
+    ) -> Self {
+        let information = ServiceInformation::new::<T, I>(type_, initializer);
+        self.register(TypeId::of::<T>(), information);
+        self.last_registered = Some(TypeId::of::<T>());
 
-        let information = ServiceInformation {
-            initialize_fn: collection_closure,
-            type_: type_.clone(),
-        };
+        self
+    }
 
-        let scoped_information = ScopedServiceInformation {
-            initialize_fn: scoped_closure,
-            type_,
-        };
+    pub fn is_registered(&self, type_id: &TypeId) -> bool {
+        self.services.contains_key(type_id)
+    }
 
-        self.services.insert(TypeId::of::<T>(), information);
-        self.scoped_services
-            .insert(TypeId::of::<T>(), scoped_information);
+    /// Inserts the registration, replacing an earlier registration of the same type.
+    /// The earlier one is still resolved by `get_services`
+    fn register(&mut self, type_id: TypeId, information: ServiceInformation) {
+        let type_name = information.type_name;
+        if let Some(replaced) = self.services.insert(type_id, information) {
+            self.duplicates.push(type_name);
+            if let Some(position) = self.instances.iter().position(|(t, _)| *t == type_id) {
+                let (_, value) = self.instances.remove(position);
+                self.keyed_instances.push((self.keyed.len(), value));
+            }
+            self.keyed.push((type_id, None, replaced));
+        }
+    }
 
+    fn last_registration(&mut self) -> &mut ServiceInformation {
+        self.last_registered
+            .and_then(|t| self.services.get_mut(&t))
+            .expect("A service has to be registered before it can be configured")
+    }
+
+    /// Applies a caching policy to the last registered service if it is transient
+    pub fn memoize(mut self, policy: Memoize) -> Self {
+        self.last_registration().memoize = Some(policy);
         self
     }
 
+    /// Registers `T` along with the dependencies and trait objects it declares
+    fn add_injectable<T: Injectable + Any + Send + Sync>(self, type_: ServiceType) -> Self {
+        let mut builder =
+            self.add_service::<T, DefaultInitializer>(type_.clone(), DefaultInitializer);
+        builder.last_registration().dependencies = T::dependencies();
+        builder.add_bindings::<T>(type_)
+    }
+
     pub fn add_singleton<T: Injectable + Any + Send + Sync>(self) -> Self {
-        self.add_service::<T, DefaultInitializer>(ServiceType::Singleton, DefaultInitializer)
+        self.add_injectable::<T>(ServiceType::Singleton)
     }
 
     pub fn add_scoped<T: Injectable + Any + Send + Sync>(self) -> Self {
-        self.add_service::<T, DefaultInitializer>(ServiceType::Scoped, DefaultInitializer)
+        self.add_injectable::<T>(ServiceType::Scoped)
     }
 
     pub fn add_transient<T: Injectable + Any + Send + Sync>(self) -> Self {
-        self.add_service::<T, DefaultInitializer>(ServiceType::Transient, DefaultInitializer)
+        self.add_injectable::<T>(ServiceType::Transient)
     }
 
-    pub fn build(self) -> ServiceCollection {
-        ServiceCollection {
-            #[allow(clippy::arc_with_non_send_sync)] // No idea why this is caused, everything looks thread safe for me :Clueless: - Instellate
-            service_info: Arc::new(self.services),
-            #[allow(clippy::arc_with_non_send_sync)]
-            scoped_service_info: Arc::new(self.scoped_services),
-            singletons: Arc::new(Default::default()),
+    /// Adds a middleware that wraps every resolution made through the container.
+    /// Middleware runs in the order it was added, before the service is initialized, so it can also
+    /// short-circuit or veto async resolutions. Middleware can't await, so for an async service that isn't
+    /// initialized yet `Next::run` returns a placeholder that is replaced by the service afterwards
+    pub fn use_resolver<F>(mut self, resolver: F) -> Self
+    where
+        F: Fn(&ResolveContext, Next) -> Result<Arc<dyn Any + Send + Sync>, Error>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.resolvers.push(Arc::new(resolver));
+        self
+    }
+
+    /// Adds an observer that gets notified about lifecycle events of the container
+    pub fn add_observer<O: Observer + 'static>(mut self, observer: O) -> Self {
+        self.observers.push(Arc::new(observer));
+        self
+    }
+
+    /// Panics if a service registered through `add_*_per_env` has no initializer for the environment,
+    /// `build_with` and `try_build` return `Error::UnmatchedEnvironment` instead
+    pub fn build(mut self) -> ServiceCollection {
+        if let Some(error) = self.select_env().into_iter().next() {
+            panic!("{error}");
+        }
+        if let Some(timeout) = self.default_timeout {
+            for information in self.services.values_mut() {
+                information.timeout.get_or_insert(timeout);
+            }
         }
+
+        let service_info = Registry::new(self.services);
+        let collection = ServiceCollection {
+            usage: Arc::new(Usage::new(service_info.clone(), self.warn_unused_on_drop)),
+            service_info,
+            root_scope_policy: self.root_scope_policy,
+            root_scope: Default::default(),
+            singletons: Arc::new(Default::default()),
+            pending_singletons: Arc::new(Default::default()),
+            memoized: Arc::new(Default::default()),
+            resolvers: Arc::new(self.resolvers),
+            observers: Arc::new(self.observers),
+            cancellation_token: self.cancellation_token.unwrap_or_default(),
+            keyed: Arc::new(keyed::KeyedServices::new(self.keyed, self.keyed_instances)),
+        };
+        collection.store_instances(self.instances);
+        collection
     }
 }
@@ -0,0 +1,67 @@
+use crate::Error;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+/// Caching policy for transient services
+#[derive(Debug, Clone)]
+pub enum Memoize {
+    /// Reuse the value for the lifetime of the scope that resolved it.
+    /// Resolving from the root collection is unaffected
+    PerScope,
+    /// Reuse the value across the whole container until it's older than the given duration
+    Ttl(Duration),
+}
+
+pub(crate) type MemoizedMap = RwLock<HashMap<TypeId, (Instant, Arc<dyn Any + Send + Sync>)>>;
+
+pub(crate) type MemoizedServices = Arc<MemoizedMap>;
+
+/// The memoized value of `type_id` unless it's older than `ttl`
+pub(crate) fn memoized_value(
+    memoized: &MemoizedServices,
+    type_id: &TypeId,
+    ttl: Duration,
+) -> Option<Arc<dyn Any + Send + Sync>> {
+    let read = memoized.read().unwrap_or_else(PoisonError::into_inner);
+    read.get(type_id)
+        .filter(|(created, _)| created.elapsed() < ttl)
+        .map(|(_, v)| v.clone())
+}
+
+fn memoize(
+    memoized: &MemoizedServices,
+    type_id: &TypeId,
+    value: Arc<dyn Any + Send + Sync>,
+) -> Arc<dyn Any + Send + Sync> {
+    let mut write = memoized.write().unwrap_or_else(PoisonError::into_inner);
+    write.insert(*type_id, (Instant::now(), value.clone()));
+    value
+}
+
+pub(crate) fn get_or_memoize(
+    memoized: &MemoizedServices,
+    type_id: &TypeId,
+    ttl: Duration,
+    initialize: impl FnOnce() -> Result<Arc<dyn Any + Send + Sync>, Error>,
+) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+    match memoized_value(memoized, type_id, ttl) {
+        Some(v) => Ok(v),
+        None => Ok(memoize(memoized, type_id, initialize()?)),
+    }
+}
+
+/// Async counterpart of `get_or_memoize`
+pub(crate) async fn get_or_memoize_async(
+    memoized: &MemoizedServices,
+    type_id: &TypeId,
+    ttl: Duration,
+    initialize: impl Future<Output = Result<Arc<dyn Any + Send + Sync>, Error>>,
+) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+    match memoized_value(memoized, type_id, ttl) {
+        Some(v) => Ok(v),
+        None => Ok(memoize(memoized, type_id, initialize.await?)),
+    }
+}
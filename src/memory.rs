@@ -0,0 +1,70 @@
+use crate::{ServiceCollection, ServiceCollectionBuilder};
+use std::any::Any;
+
+/// Estimate of the memory held by a service, including its heap allocations.
+/// Reported by `memory_report` for services registered with `track_memory`
+pub trait MemoryFootprint {
+    fn memory_footprint(&self) -> usize;
+}
+
+pub(crate) type FootprintFn = fn(&(dyn Any + Send + Sync)) -> Option<usize>;
+
+/// Memory attributed to one initialized service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceMemory {
+    pub type_name: &'static str,
+    pub bytes: usize,
+    /// `false` if the service doesn't report its footprint and only its inline size is known
+    pub reported: bool,
+}
+
+/// Memory held by the initialized singletons, largest first
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MemoryReport {
+    pub services: Vec<ServiceMemory>,
+    pub total: usize,
+}
+
+impl ServiceCollectionBuilder {
+    /// Reports the memory footprint of the last registered service, which has to be `T`, in `memory_report`
+    pub fn track_memory<T: MemoryFootprint + Any + Send + Sync>(mut self) -> Self {
+        self.last_registration().memory_footprint = Some(|value| {
+            value
+                .downcast_ref::<T>()
+                .map(MemoryFootprint::memory_footprint)
+        });
+        self
+    }
+}
+
+impl ServiceCollection {
+    /// Approximates the memory held by every initialized singleton.
+    /// Services registered with `track_memory` report their own footprint,
+    /// the others are counted with their inline size only
+    pub fn memory_report(&self) -> MemoryReport {
+        let services = self.service_info.snapshot();
+        let mut report = MemoryReport::default();
+
+        for (type_id, value) in self.singletons.snapshot().iter() {
+            let Some(information) = services.get(type_id) else {
+                continue;
+            };
+
+            let footprint = information
+                .memory_footprint
+                .and_then(|footprint| footprint(value.as_ref()));
+            let service = ServiceMemory {
+                type_name: information.type_name,
+                bytes: footprint.unwrap_or_else(|| std::mem::size_of_val(value.as_ref())),
+                reported: footprint.is_some(),
+            };
+            report.total += service.bytes;
+            report.services.push(service);
+        }
+
+        report
+            .services
+            .sort_by_key(|service| std::cmp::Reverse(service.bytes));
+        report
+    }
+}
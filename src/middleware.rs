@@ -0,0 +1,51 @@
+use crate::{Error, ServiceType};
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+/// Middleware wrapping every resolution.
+/// Receives the context of the resolution and the rest of the chain,
+/// and can short-circuit, substitute or decorate the result.
+pub type Resolver =
+    Arc<dyn Fn(&ResolveContext, Next) -> Result<Arc<dyn Any + Send + Sync>, Error> + Send + Sync>;
+
+/// Information about the service that is currently being resolved
+#[derive(Debug, Clone)]
+pub struct ResolveContext<'a> {
+    pub type_id: &'a TypeId,
+    /// `None` if the service isn't registered in the container
+    pub type_name: Option<&'static str>,
+    /// `None` if the service isn't registered in the container
+    pub service_type: Option<ServiceType>,
+}
+
+/// The remaining part of the resolver chain
+pub struct Next<'a> {
+    resolvers: &'a [Resolver],
+    context: &'a ResolveContext<'a>,
+    resolve: &'a dyn Fn() -> Result<Arc<dyn Any + Send + Sync>, Error>,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(
+        resolvers: &'a [Resolver],
+        context: &'a ResolveContext<'a>,
+        resolve: &'a dyn Fn() -> Result<Arc<dyn Any + Send + Sync>, Error>,
+    ) -> Self {
+        Self {
+            resolvers,
+            context,
+            resolve,
+        }
+    }
+
+    /// Runs the next resolver, or the container itself if this is the end of the chain.
+    /// For an async service that isn't initialized yet, it returns a placeholder the initialized service replaces
+    pub fn run(self) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        match self.resolvers.split_first() {
+            Some((resolver, rest)) => {
+                resolver(self.context, Next::new(rest, self.context, self.resolve))
+            }
+            None => (self.resolve)(),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Mocks, e.g. generated by mockall, standing in for trait object services in tests.
+//! `builder.add_mock::<dyn Mailer, _>(&mailer, |m| m)` registers the mock held by `mailer: MockDep<MockMailer>`,
+//! whose expectations can still be set through `mailer.expect(|m| ...)` until the container resolves it
+
+use crate::trait_object::{trait_object_information, Implementation, TraitObject};
+use crate::{Dep, Initialize, ServiceCollectionBuilder, ServiceHandler, ServiceType};
+use std::any::{Any, TypeId};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+struct MockState<M> {
+    /// The mock until it's resolved for the first time
+    pending: Mutex<Option<M>>,
+    resolved: OnceLock<Arc<M>>,
+}
+
+/// Handle to a mock registered with `add_mock`. Clones share the mock
+pub struct MockDep<M> {
+    state: Arc<MockState<M>>,
+}
+
+impl<M> Clone for MockDep<M> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<M> MockDep<M> {
+    pub fn new(mock: M) -> Self {
+        Self {
+            state: Arc::new(MockState {
+                pending: Mutex::new(Some(mock)),
+                resolved: OnceLock::new(),
+            }),
+        }
+    }
+
+    /// Calls `f` with the mock to set its expectations, e.g. `mailer.expect(|m| { m.expect_send().times(1); })`.
+    /// Panics once the container resolved the mock, as it's shared with the services using it from then on
+    #[track_caller]
+    pub fn expect<R>(&self, f: impl FnOnce(&mut M) -> R) -> R {
+        let mut pending = self
+            .state
+            .pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        match pending.as_mut() {
+            Some(mock) => f(mock),
+            None => panic!(
+                "Expectations of {} have to be set before it's resolved",
+                std::any::type_name::<M>()
+            ),
+        }
+    }
+
+    /// The mock as resolved by the container, `None` if it wasn't resolved yet
+    pub fn resolved(&self) -> Option<Dep<M>> {
+        self.state.resolved.get().cloned().map(Dep)
+    }
+}
+
+/// Moves the mock into the container on the first resolution as the trait object `T`
+struct MockInitializer<M, T: ?Sized> {
+    mock: MockDep<M>,
+    cast: fn(Arc<M>) -> Arc<T>,
+}
+
+impl<M, T: ?Sized> Clone for MockInitializer<M, T> {
+    fn clone(&self) -> Self {
+        Self {
+            mock: self.mock.clone(),
+            cast: self.cast,
+        }
+    }
+}
+
+impl<M: Any + Send + Sync, T: ?Sized + Send + Sync + 'static> Initialize<TraitObject<T>>
+    for MockInitializer<M, T>
+{
+    fn initialize<H: ServiceHandler>(&self, _handler: &H) -> TraitObject<T> {
+        let state = &self.mock.state;
+        let mock = state.resolved.get_or_init(|| {
+            let mock = state
+                .pending
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+            Arc::new(mock.expect("The mock is only taken once"))
+        });
+        TraitObject::cast(mock.clone(), self.cast)
+    }
+}
+
+impl ServiceCollectionBuilder {
+    /// Registers `mock` as the singleton implementation of the trait object `T`, replacing the real one.
+    /// `cast` converts the mock into the trait object, usually `|m| m`.
+    /// The mock can also be resolved through `get_implementation::<M>()`
+    pub fn add_mock<T, M>(mut self, mock: &MockDep<M>, cast: fn(Arc<M>) -> Arc<T>) -> Self
+    where
+        T: ?Sized + Send + Sync + 'static,
+        M: Any + Send + Sync,
+    {
+        let initializer = MockInitializer {
+            mock: mock.clone(),
+            cast,
+        };
+        let mut information = trait_object_information::<T, _>(ServiceType::Singleton, initializer);
+        information.implementation = Some(Implementation::of::<T, M>());
+
+        self.register(TypeId::of::<T>(), information);
+        self.last_registered = Some(TypeId::of::<T>());
+
+        self
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{CancellationToken, ScopeId};
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+/// Read-only observer of the container's lifecycle.
+/// Every method has an empty default implementation so only the relevant events have to be implemented
+pub trait Observer: Send + Sync {
+    /// Called after a service was successfully resolved
+    fn on_resolved(&self, _type_id: &TypeId) {}
+
+    fn on_scope_created(&self) {}
+
+    /// Called when the last handle to a scope is dropped
+    fn on_scope_dropped(&self) {}
+
+    /// Called after a singleton was initialized for the first time
+    fn on_singleton_created(&self, _type_id: &TypeId) {}
+}
+
+pub(crate) type Observers = Arc<Vec<Arc<dyn Observer>>>;
+
+/// Shared between all clones of a scope, notifies observers when the scope is created and dropped
+pub(crate) struct ScopeLifecycle {
+    pub(crate) observers: Observers,
+    /// `scope_id` as a service, so resolving it doesn't allocate
+    pub(crate) id_service: Arc<dyn Any + Send + Sync>,
+    scope_id: ScopeId,
+    /// Cancelled when the scope is dropped or the collection's token is cancelled
+    pub(crate) cancellation_token: CancellationToken,
+    /// `cancellation_token` as a service
+    pub(crate) cancellation_service: Arc<dyn Any + Send + Sync>,
+    /// `WeakProvider` of the collection the scope was created from, as a service
+    pub(crate) root_service: Arc<dyn Any + Send + Sync>,
+    #[cfg(feature = "profiling")]
+    created: std::time::Instant,
+}
+
+impl ScopeLifecycle {
+    pub(crate) fn new(
+        observers: Observers,
+        parent_token: &CancellationToken,
+        root_service: Arc<dyn Any + Send + Sync>,
+    ) -> Self {
+        let scope_id = ScopeId::next();
+        #[cfg(feature = "tracing")]
+        tracing::debug!(scope_id = %scope_id, "Scope created");
+
+        for observer in observers.iter() {
+            observer.on_scope_created();
+        }
+
+        let cancellation_token = parent_token.child_token();
+        let cancellation_service = Arc::new(cancellation_token.clone());
+        Self {
+            observers,
+            id_service: Arc::new(scope_id),
+            scope_id,
+            cancellation_token,
+            cancellation_service,
+            root_service,
+            #[cfg(feature = "profiling")]
+            created: std::time::Instant::now(),
+        }
+    }
+
+    pub(crate) fn scope_id(&self) -> ScopeId {
+        self.scope_id
+    }
+}
+
+impl Drop for ScopeLifecycle {
+    fn drop(&mut self) {
+        self.cancellation_token.cancel();
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(scope_id = %self.scope_id, "Scope dropped");
+
+        for observer in self.observers.iter() {
+            observer.on_scope_dropped();
+        }
+
+        #[cfg(feature = "profiling")]
+        crate::profiling::record_scope(self.scope_id, self.created);
+    }
+}
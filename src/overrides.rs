@@ -0,0 +1,57 @@
+use crate::trait_object::TraitObject;
+use crate::{ServiceCollection, ServiceScope};
+use indexmap::IndexMap;
+use std::any::{Any, TypeId};
+use std::borrow::Cow;
+use std::sync::Arc;
+
+pub(crate) type Overrides = Arc<IndexMap<TypeId, Arc<dyn Any + Send + Sync>>>;
+
+/// Services substituted in a single scope, see `ServiceCollection::create_scope_with_overrides`
+#[derive(Default)]
+pub struct ScopeOverrides {
+    services: IndexMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl ScopeOverrides {
+    /// Resolves `value` as `T` in the scope, whether or not `T` is registered
+    pub fn replace<T: Any + Send + Sync>(mut self, value: T) -> Self {
+        self.services.insert(TypeId::of::<T>(), Arc::new(value));
+        self
+    }
+
+    /// Resolves `value` as the trait object `T` in the scope, e.g. `replace_dyn::<dyn Clock>(Arc::new(clock))`
+    pub fn replace_dyn<T: ?Sized + Send + Sync + 'static>(mut self, value: Arc<T>) -> Self {
+        self.services
+            .insert(TypeId::of::<T>(), Arc::new(TraitObject::from_object(value)));
+        self
+    }
+}
+
+impl ServiceScope {
+    /// Handler initializing services shared with other scopes, e.g. singletons, without the overrides of this scope
+    pub(crate) fn shared_handler(&self) -> Cow<'_, ServiceScope> {
+        if self.overrides.is_empty() {
+            Cow::Borrowed(self)
+        } else {
+            let mut scope = self.clone();
+            scope.overrides = Default::default();
+            Cow::Owned(scope)
+        }
+    }
+}
+
+impl ServiceCollection {
+    /// Creates a scope that resolves the services replaced by `f` instead of their registrations,
+    /// e.g. `create_scope_with_overrides(|o| o.replace_dyn::<dyn Clock>(frozen_clock))`.
+    /// Scoped and transient services created by the scope are injected with the replacements,
+    /// singletons are shared with the collection and keep their dependencies
+    pub fn create_scope_with_overrides(
+        &self,
+        f: impl FnOnce(ScopeOverrides) -> ScopeOverrides,
+    ) -> ServiceScope {
+        let mut scope = ServiceScope::create(self);
+        scope.overrides = Arc::new(f(ScopeOverrides::default()).services);
+        scope
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{Error, ServiceCollectionBuilder, ServiceInformation};
+
+/// Where a service may be resolved from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Placement {
+    #[default]
+    Anywhere,
+    ScopeOnly,
+    RootOnly,
+}
+
+impl ServiceCollectionBuilder {
+    /// Only allows the last registered service to be resolved from a scope, e.g. a request context.
+    /// Resolving it from the collection fails with `Error::ScopeOnly`
+    pub fn scope_only(mut self) -> Self {
+        self.last_registration().placement = Placement::ScopeOnly;
+        self
+    }
+
+    /// Only allows the last registered service to be resolved from the collection,
+    /// e.g. infrastructure that lives as long as the application.
+    /// Resolving it from a scope fails with `Error::RootOnly`
+    pub fn root_only(mut self) -> Self {
+        self.last_registration().placement = Placement::RootOnly;
+        self
+    }
+}
+
+impl ServiceInformation {
+    /// Fails if the service may not be resolved from a scope, or from the collection if `in_scope` is `false`
+    pub(crate) fn check_placement(&self, in_scope: bool) -> Result<(), Error> {
+        match (self.placement, in_scope) {
+            (Placement::ScopeOnly, false) => Err(Error::ScopeOnly(self.type_name)),
+            (Placement::RootOnly, true) => Err(Error::RootOnly(self.type_name)),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Records service initializations and scope lifetimes in the Chrome trace format,
+//! which `chrome://tracing` and Perfetto can open
+
+use crate::ScopeId;
+use std::fmt::Write as _;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+
+struct Recording {
+    origin: Instant,
+    events: Vec<Event>,
+}
+
+enum Track {
+    /// Synchronous initializations nest on the thread that runs them
+    Thread(u64),
+    /// Async initializations and scopes overlap freely, so each gets its own track
+    Async(u64),
+}
+
+struct Event {
+    name: String,
+    category: &'static str,
+    track: Track,
+    start: Duration,
+    duration: Duration,
+}
+
+fn thread_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    thread_local! {
+        static ID: u64 = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+    ID.with(|id| *id)
+}
+
+fn async_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+fn record(name: impl Into<String>, category: &'static str, track: Track, started: Instant) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let duration = started.elapsed();
+    let mut recording = RECORDING.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(recording) = recording.as_mut() {
+        recording.events.push(Event {
+            name: name.into(),
+            category,
+            track,
+            start: started.saturating_duration_since(recording.origin),
+            duration,
+        });
+    }
+}
+
+pub(crate) fn record_initialization(type_name: &'static str, started: Instant) {
+    record(type_name, "initialize", Track::Thread(thread_id()), started);
+}
+
+pub(crate) fn record_async_initialization(type_name: &'static str, started: Instant) {
+    record(
+        type_name,
+        "initialize_async",
+        Track::Async(async_id()),
+        started,
+    );
+}
+
+pub(crate) fn record_scope(scope_id: ScopeId, created: Instant) {
+    record(
+        format!("scope {scope_id}"),
+        "scope",
+        Track::Async(async_id()),
+        created,
+    );
+}
+
+/// Starts recording, discarding the events of a previous recording
+pub fn start() {
+    *RECORDING.lock().unwrap_or_else(PoisonError::into_inner) = Some(Recording {
+        origin: Instant::now(),
+        events: Vec::new(),
+    });
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Stops recording, the recorded events can still be exported
+pub fn stop() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Events recorded since `start` as Chrome trace JSON
+pub fn chrome_trace() -> String {
+    let recording = RECORDING.lock().unwrap_or_else(PoisonError::into_inner);
+    let events = recording.as_ref().map_or(&[][..], |r| &r.events);
+
+    let mut json = String::from("{\"traceEvents\":[");
+    for (i, event) in events.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+
+        let name = escape(&event.name);
+        let start = event.start.as_micros();
+        let end = (event.start + event.duration).as_micros();
+        // Writing to a `String` can't fail
+        let _ = match event.track {
+            Track::Thread(tid) => write!(
+                json,
+                "{{\"name\":\"{name}\",\"cat\":\"{}\",\"ph\":\"X\",\"ts\":{start},\"dur\":{},\"pid\":1,\"tid\":{tid}}}",
+                event.category,
+                end - start,
+            ),
+            Track::Async(id) => write!(
+                json,
+                "{{\"name\":\"{name}\",\"cat\":\"{0}\",\"ph\":\"b\",\"ts\":{start},\"id\":{id},\"pid\":1,\"tid\":0}},\
+                 {{\"name\":\"{name}\",\"cat\":\"{0}\",\"ph\":\"e\",\"ts\":{end},\"id\":{id},\"pid\":1,\"tid\":0}}",
+                event.category,
+            ),
+        };
+    }
+    json.push_str("]}");
+    json
+}
+
+/// Writes the events recorded since `start` as Chrome trace JSON
+pub fn write_chrome_trace<W: io::Write>(mut writer: W) -> io::Result<()> {
+    writer.write_all(chrome_trace().as_bytes())
+}
@@ -0,0 +1,7 @@
+use crate::Dep;
+
+/// Implemented by containers declared with `deppy_macros::container!` for every service they register,
+/// so functions can require the services they need at compile time, e.g. `C: Provides<Database>`
+pub trait Provides<T: ?Sized> {
+    fn provide(&self) -> Dep<T>;
+}
@@ -0,0 +1,40 @@
+use crate::{ServiceCollection, ServiceScope};
+use std::any::TypeId;
+use std::sync::PoisonError;
+
+impl ServiceCollection {
+    /// Discards the initialized singleton or memoized value of `T`, including its keyed and replaced registrations,
+    /// so the next resolution creates a new one,
+    /// e.g. after the configuration it was created from changed.
+    /// Services already holding the old value keep it. Returns whether a value was discarded
+    pub fn reload<T: ?Sized + 'static>(&self) -> bool {
+        let type_id = TypeId::of::<T>();
+        let memoized = self
+            .memoized
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&type_id)
+            .is_some();
+        self.singletons.remove(&type_id) | self.keyed.reload(&type_id) | memoized
+    }
+}
+
+impl ServiceScope {
+    /// Discards the value of `T` cached by this scope, or shared with the collection for singletons,
+    /// so the next resolution creates a new one. Returns whether a value was discarded
+    pub fn reload<T: ?Sized + 'static>(&self) -> bool {
+        let type_id = TypeId::of::<T>();
+        let memoized = self
+            .memoized
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&type_id)
+            .is_some();
+        let keyed_scoped = self.keyed_scoped.remove(self.keyed.of_type(&type_id));
+        self.singletons.remove(&type_id)
+            | self.scoped.remove(&type_id)
+            | self.keyed.reload(&type_id)
+            | keyed_scoped
+            | memoized
+    }
+}
@@ -0,0 +1,56 @@
+use crate::Error;
+use std::time::Duration;
+
+/// Retry policy for async initializers and jobs.
+/// Only initializer failures, failed jobs and timeouts are retried, missing services fail right away
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub(crate) attempts: u32,
+    pub(crate) backoff: Backoff,
+}
+
+#[derive(Debug, Clone)]
+pub enum Backoff {
+    Fixed(Duration),
+    /// Doubles the delay after every failed attempt, up to `max`
+    Exponential {
+        initial: Duration,
+        max: Duration,
+    },
+}
+
+impl RetryPolicy {
+    /// Retries up to `attempts` times in total, waiting `delay` between each attempt
+    pub fn fixed(attempts: u32, delay: Duration) -> Self {
+        Self {
+            attempts,
+            backoff: Backoff::Fixed(delay),
+        }
+    }
+
+    /// Retries up to `attempts` times in total, doubling the delay between each attempt
+    pub fn exponential(attempts: u32, initial: Duration, max: Duration) -> Self {
+        Self {
+            attempts,
+            backoff: Backoff::Exponential { initial, max },
+        }
+    }
+
+    pub(crate) fn should_retry(&self, attempt: u32, error: &Error) -> bool {
+        attempt < self.attempts
+            && matches!(
+                error,
+                Error::Timeout(_) | Error::InitializationFailed { .. } | Error::JobFailed { .. }
+            )
+    }
+
+    /// Delay before the given attempt, counting from 1
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        match self.backoff {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { initial, max } => initial
+                .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+                .min(max),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Hands embedded Rhai scripts controlled access to the services of a scope
+
+use crate::{RegistrationSummary, ServiceHandler, ServiceScope};
+use indexmap::IndexMap;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map};
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+type ResolveFn = fn(&ServiceScope) -> Option<Dynamic>;
+
+struct Exposed {
+    type_id: TypeId,
+    resolve: ResolveFn,
+}
+
+/// Services scripts may resolve, by the name of their type without its path, e.g. `resolve("Mailer")`.
+/// Services are returned as `Dep<T>`, their methods have to be registered on the `Engine` as usual
+pub struct ScriptBindings {
+    scope: ServiceScope,
+    exposed: IndexMap<String, Exposed>,
+}
+
+/// `crate::module::Mailer<T>` becomes `Mailer<T>`
+fn script_name(type_name: &str) -> String {
+    let (path, generics) = type_name.split_at(type_name.find('<').unwrap_or(type_name.len()));
+    let name = path.rsplit("::").next().unwrap_or(path);
+    format!("{name}{generics}")
+}
+
+impl ScriptBindings {
+    pub fn new(scope: impl Into<ServiceScope>) -> Self {
+        Self {
+            scope: scope.into(),
+            exposed: IndexMap::new(),
+        }
+    }
+
+    /// Allows scripts to resolve `T`, services that aren't exposed can't be reached from scripts
+    pub fn expose<T: Any + Send + Sync>(mut self) -> Self {
+        self.exposed.insert(
+            script_name(std::any::type_name::<T>()),
+            Exposed {
+                type_id: TypeId::of::<T>(),
+                resolve: |scope| ServiceHandler::get_service::<T>(scope).map(Dynamic::from),
+            },
+        );
+        self
+    }
+
+    /// Registers `resolve(name)` and `services()` on the engine.
+    /// `services()` returns the registrations of the exposed services as maps
+    /// with `name`, `type_name` and `lifetime`
+    pub fn register(self, engine: &mut Engine) {
+        let bindings = Arc::new(self);
+
+        let resolve = bindings.clone();
+        engine.register_fn(
+            "resolve",
+            move |name: &str| -> Result<Dynamic, Box<EvalAltResult>> {
+                let exposed = resolve
+                    .exposed
+                    .get(name)
+                    .ok_or_else(|| format!("Service {name} isn't exposed to scripts"))?;
+                (exposed.resolve)(&resolve.scope)
+                    .ok_or_else(|| format!("Service {name} couldn't be resolved").into())
+            },
+        );
+
+        engine.register_fn("services", move || -> Array {
+            let services = bindings.scope.services.snapshot();
+            bindings
+                .exposed
+                .iter()
+                .filter_map(|(name, exposed)| {
+                    let summary = RegistrationSummary::from(services.get(&exposed.type_id)?);
+                    let mut map = Map::new();
+                    map.insert("name".into(), name.clone().into());
+                    map.insert("type_name".into(), summary.type_name.into());
+                    map.insert(
+                        "lifetime".into(),
+                        format!("{:?}", summary.type_).to_lowercase().into(),
+                    );
+                    Some(Dynamic::from_map(map))
+                })
+                .collect()
+        });
+    }
+}
@@ -0,0 +1,93 @@
+//! Request scoped services for Rocket, e.g.
+//! `rocket::build().attach(ServicesFairing::new(collection))` with handlers taking `Inject<UserService>`
+
+use crate::{Dep, Error, ServiceCollection, ServiceHandler, ServiceScope};
+use ::rocket::fairing::{self, Fairing, Info, Kind};
+use ::rocket::http::Status;
+use ::rocket::request::{FromRequest, Outcome};
+use ::rocket::{Build, Request, Response, Rocket};
+use std::any::{type_name, Any};
+use std::ops::Deref;
+
+/// Manages the collection as Rocket state and closes the scope of every request
+/// with `ServiceScope::close` once its response is ready
+pub struct ServicesFairing {
+    collection: ServiceCollection,
+}
+
+impl ServicesFairing {
+    pub fn new(collection: ServiceCollection) -> Self {
+        Self { collection }
+    }
+}
+
+/// The scope of a request, created by the first guard that needs it
+struct RequestScope(Option<ServiceScope>);
+
+#[::rocket::async_trait]
+impl Fairing for ServicesFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "deppy services",
+            kind: Kind::Ignite | Kind::Response,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        Ok(rocket.manage(self.collection.clone()))
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, _response: &mut Response<'r>) {
+        if let RequestScope(Some(scope)) = request.local_cache(|| RequestScope(None)) {
+            scope.clone().close().await;
+        }
+    }
+}
+
+/// Returns the scope of the request, creating it from the managed collection on first use
+fn request_scope<'r>(request: &'r Request<'_>) -> Result<&'r ServiceScope, Error> {
+    let collection = request.rocket().state::<ServiceCollection>();
+    let RequestScope(scope) =
+        request.local_cache(|| RequestScope(collection.map(ServiceCollection::create_scope)));
+
+    scope.as_ref().ok_or(Error::NotRegistered {
+        type_name: Some(type_name::<ServiceCollection>()),
+        requested_by: None,
+    })
+}
+
+/// Request guard resolving `T` from the scope of the request.
+/// Fails the request with `500 Internal Server Error` if `T` can't be resolved or `ServicesFairing` isn't attached
+pub struct Inject<T: ?Sized>(pub Dep<T>);
+
+impl<T: ?Sized> Deref for Inject<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[::rocket::async_trait]
+impl<'r, T: Any + Send + Sync> FromRequest<'r> for Inject<T> {
+    type Error = Error;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request_scope(request).and_then(|scope| scope.try_get_service()) {
+            Ok(service) => Outcome::Success(Inject(service)),
+            Err(error) => Outcome::Error((Status::InternalServerError, error)),
+        }
+    }
+}
+
+#[::rocket::async_trait]
+impl<'r> FromRequest<'r> for ServiceScope {
+    type Error = Error;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request_scope(request) {
+            Ok(scope) => Outcome::Success(scope.clone()),
+            Err(error) => Outcome::Error((Status::InternalServerError, error)),
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use crate::{ServiceCollection, ServiceCollectionBuilder, ServiceScope};
+
+/// What resolving a scoped service directly from the `ServiceCollection` does
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RootScopePolicy {
+    /// Creates a new instance on every resolution, like a transient service
+    #[default]
+    Transient,
+    /// The collection acts as a scope of its own that lives as long as the collection
+    ImplicitScope,
+    /// Resolution fails with `Error::ScopedFromRoot`
+    Error,
+}
+
+impl ServiceCollectionBuilder {
+    /// Sets what resolving a scoped service from the collection instead of a scope does
+    pub fn root_scope_policy(mut self, policy: RootScopePolicy) -> Self {
+        self.root_scope_policy = policy;
+        self
+    }
+}
+
+impl ServiceCollection {
+    /// Scope backing `RootScopePolicy::ImplicitScope`, created on first use
+    pub(crate) fn root_scope(&self) -> &ServiceScope {
+        self.root_scope.get_or_init(|| ServiceScope::create(self))
+    }
+}
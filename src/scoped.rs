@@ -0,0 +1,18 @@
+/// Runs a block in a new scope with the listed services resolved from it, then drops the scope.
+/// Each service is bound as a `Dep`, panicking if it can't be resolved,
+/// e.g. `scoped!(collection, |mailer: Mailer, users: UserRepository| { ... })`
+#[macro_export]
+macro_rules! scoped {
+    ($collection:expr, || $body:block) => {
+        $crate::ServiceCollection::run_scoped(&$collection, |_| $body)
+    };
+    ($collection:expr, |$($name:ident: $service:ty),* $(,)?| $body:block) => {
+        $crate::ServiceCollection::run_scoped(&$collection, |scope| {
+            $(
+                let $name: $crate::Dep<$service> =
+                    $crate::ServiceHandler::get_required_service::<$service>(scope);
+            )*
+            $body
+        })
+    };
+}
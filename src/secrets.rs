@@ -0,0 +1,138 @@
+use crate::{
+    BoxFuture, Dep, Dependency, Error, Initialize, Injectable, ServiceCollectionBuilder,
+    ServiceHandler, ServiceType,
+};
+use std::error::Error as StdError;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Source of secret values, e.g. environment variables, mounted files or a secret manager like Vault or SSM.
+/// Implement it to fetch secrets from another store and register it with `add_secrets_provider`
+pub trait SecretsProvider: Send + Sync {
+    fn fetch(&self, name: &str) -> BoxFuture<'_, Result<String, Box<dyn StdError + Send + Sync>>>;
+}
+
+/// Names a secret, e.g. `impl SecretKey for DatabasePassword { const NAME: &'static str = "DATABASE_PASSWORD"; }`
+pub trait SecretKey: Send + Sync + 'static {
+    const NAME: &'static str;
+}
+
+/// Reads the secret from the environment variable `{prefix}{name}`
+#[derive(Debug, Clone, Default)]
+pub struct EnvSecrets {
+    prefix: String,
+}
+
+impl EnvSecrets {
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+}
+
+impl SecretsProvider for EnvSecrets {
+    fn fetch(&self, name: &str) -> BoxFuture<'_, Result<String, Box<dyn StdError + Send + Sync>>> {
+        let variable = format!("{}{name}", self.prefix);
+        Box::pin(async move {
+            std::env::var(&variable)
+                .map_err(|e| format!("Secret {variable} couldn't be read: {e}").into())
+        })
+    }
+}
+
+/// Reads the secret from the file `name` in a directory, e.g. `/run/secrets` for Docker secrets.
+/// A trailing newline is removed
+#[derive(Debug, Clone)]
+pub struct FileSecrets {
+    directory: PathBuf,
+}
+
+impl FileSecrets {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+}
+
+impl SecretsProvider for FileSecrets {
+    fn fetch(&self, name: &str) -> BoxFuture<'_, Result<String, Box<dyn StdError + Send + Sync>>> {
+        let path = self.directory.join(name);
+        Box::pin(async move {
+            let value = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Secret {} couldn't be read: {e}", path.display()))?;
+            Ok(value.strip_suffix('\n').unwrap_or(&value).to_owned())
+        })
+    }
+}
+
+/// Secret `K`, fetched from the registered `SecretsProvider` on first use and cached afterwards.
+/// The value never shows up in `Debug` output
+pub struct Secret<K> {
+    provider: Dep<dyn SecretsProvider>,
+    value: OnceCell<String>,
+    key: PhantomData<fn() -> K>,
+}
+
+impl<K: SecretKey> Secret<K> {
+    /// Fetches the secret the first time it's called, a failed fetch is retried on the next call
+    pub async fn expose(&self) -> Result<&str, Error> {
+        self.value
+            .get_or_try_init(|| async {
+                self.provider
+                    .fetch(K::NAME)
+                    .await
+                    .map_err(|source| Error::InitializationFailed { source })
+            })
+            .await
+            .map(String::as_str)
+    }
+}
+
+impl<K: SecretKey> Debug for Secret<K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret({}: <redacted>)", K::NAME)
+    }
+}
+
+impl<K: SecretKey> Injectable for Secret<K> {
+    fn inject<T: ServiceHandler>(handler: &T) -> Self {
+        Self {
+            provider: handler.get_required_dyn_service(),
+            value: OnceCell::new(),
+            key: PhantomData,
+        }
+    }
+
+    fn dependencies() -> Vec<Dependency> {
+        vec![Dependency::of::<dyn SecretsProvider>()]
+    }
+}
+
+#[derive(Clone)]
+struct SharedProvider(Arc<dyn SecretsProvider>);
+
+impl Initialize<Arc<dyn SecretsProvider>> for SharedProvider {
+    fn initialize<T: ServiceHandler>(&self, _handler: &T) -> Arc<dyn SecretsProvider> {
+        self.0.clone()
+    }
+}
+
+impl ServiceCollectionBuilder {
+    /// Registers the provider secrets are fetched from, resolvable as `Dep<dyn SecretsProvider>`
+    pub fn add_secrets_provider<P: SecretsProvider + 'static>(self, provider: P) -> Self {
+        self.add_service_as::<dyn SecretsProvider, _>(
+            ServiceType::Singleton,
+            SharedProvider(Arc::new(provider)),
+        )
+    }
+
+    /// Registers `Secret<K>` as a singleton, so its value is fetched at most once
+    pub fn add_secret<K: SecretKey>(self) -> Self {
+        self.add_singleton::<Secret<K>>()
+    }
+}
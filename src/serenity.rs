@@ -0,0 +1,73 @@
+//! Event scoped services for serenity Discord bots, e.g.
+//! `Client::builder(token, intents).services(collection, handler)` with `handler` implementing `ScopedEventHandler`
+
+use crate::{ServiceCollection, ServiceHandler, ServiceScope};
+use ::serenity::all::{Context, Event, RawEventHandler};
+use ::serenity::async_trait;
+use ::serenity::client::ClientBuilder;
+use ::serenity::prelude::TypeMapKey;
+
+/// Key of the collection in the client's data, see `services`
+pub struct Services;
+
+impl TypeMapKey for Services {
+    type Value = ServiceCollection;
+}
+
+/// Returns the collection registered with `ClientBuilderExt::services`, e.g. to resolve singletons
+/// in handlers that don't go through `ScopedEventHandler`
+pub async fn services(ctx: &Context) -> Option<ServiceCollection> {
+    ctx.data.read().await.get::<Services>().cloned()
+}
+
+/// Handles every gateway event, including interactions, with a scope created for that event
+#[async_trait]
+pub trait ScopedEventHandler: Send + Sync {
+    async fn event(&self, ctx: Context, event: Event, scope: &ServiceScope);
+}
+
+/// Raw event handler creating a scope per event and closing it with `ServiceScope::close`
+/// once `H` has handled the event
+pub struct ScopedEvents<H> {
+    collection: ServiceCollection,
+    handler: H,
+}
+
+impl<H: ScopedEventHandler> ScopedEvents<H> {
+    pub fn new(collection: ServiceCollection, handler: H) -> Self {
+        Self {
+            collection,
+            handler,
+        }
+    }
+}
+
+#[async_trait]
+impl<H: ScopedEventHandler> RawEventHandler for ScopedEvents<H> {
+    async fn raw_event(&self, ctx: Context, event: Event) {
+        let scope = self.collection.create_scope();
+        self.handler.event(ctx, event, &scope).await;
+        scope.close().await;
+    }
+}
+
+/// Adds `services` to serenity's `ClientBuilder`
+pub trait ClientBuilderExt {
+    /// Stores the collection in the client's data and dispatches events to `handler` with `ScopedEvents`
+    fn services<H: ScopedEventHandler + 'static>(
+        self,
+        collection: ServiceCollection,
+        handler: H,
+    ) -> Self;
+}
+
+impl ClientBuilderExt for ClientBuilder {
+    fn services<H: ScopedEventHandler + 'static>(
+        self,
+        collection: ServiceCollection,
+        handler: H,
+    ) -> Self {
+        self.type_map_insert::<Services>(collection.clone())
+            .raw_event_handler(ScopedEvents::new(collection, handler))
+    }
+}
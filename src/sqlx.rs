@@ -0,0 +1,141 @@
+//! sqlx pools as async singletons and a transaction per scope, e.g.
+//! `builder.add_pool::<Postgres>(PoolOptions::new(), url).add_transaction::<Postgres>()`
+//! registers a `PgPool` and a `DbTransaction<Postgres>`, committed with `DbTransaction::commit`
+//! or by a `UnitOfWork` it's enlisted in. Work that wasn't committed is rolled back when the scope is closed
+
+use crate::{
+    AsyncDispose, AsyncInitialize, AsyncInjectable, AsyncServiceHandler, BoxFuture, Dep, Error,
+    Participant, ServiceCollectionBuilder, ServiceType,
+};
+use ::sqlx::pool::{Pool, PoolOptions};
+use ::sqlx::{Database, Transaction};
+use std::error::Error as StdError;
+use std::sync::Arc;
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
+
+struct PoolInitializer<DB: Database> {
+    options: PoolOptions<DB>,
+    url: Arc<str>,
+}
+
+// Derived `Clone` would require `DB: Clone`
+impl<DB: Database> Clone for PoolInitializer<DB> {
+    fn clone(&self) -> Self {
+        Self {
+            options: self.options.clone(),
+            url: self.url.clone(),
+        }
+    }
+}
+
+impl<DB: Database> AsyncInitialize<Pool<DB>> for PoolInitializer<DB> {
+    async fn initialize<T: AsyncServiceHandler>(&self, _handler: &T) -> Result<Pool<DB>, Error> {
+        self.options
+            .clone()
+            .connect(&self.url)
+            .await
+            .map_err(|e| Error::InitializationFailed {
+                source: Box::new(e),
+            })
+    }
+}
+
+/// Transaction of a scope, begun on the first call to `get`.
+/// It's only committed by `commit`, `ServiceScope::close` and dropping the scope roll back what wasn't committed
+pub struct DbTransaction<DB: Database> {
+    pool: Dep<Pool<DB>>,
+    transaction: Mutex<Option<Transaction<'static, DB>>>,
+}
+
+impl<DB: Database> DbTransaction<DB> {
+    /// Returns the transaction, beginning it if it's the first use in the scope or after `commit` or `rollback`.
+    /// Execute queries on it with `&mut **transaction`
+    pub async fn get(
+        &self,
+    ) -> Result<MappedMutexGuard<'_, Transaction<'static, DB>>, ::sqlx::Error> {
+        let mut transaction = self.transaction.lock().await;
+        if transaction.is_none() {
+            *transaction = Some(self.pool.begin().await?);
+        }
+
+        Ok(MutexGuard::map(transaction, |transaction| {
+            transaction.as_mut().expect("transaction was just begun")
+        }))
+    }
+
+    /// Commits the transaction if one was begun
+    pub async fn commit(&self) -> Result<(), ::sqlx::Error> {
+        match self.transaction.lock().await.take() {
+            Some(transaction) => transaction.commit().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Rolls back the transaction if one was begun
+    pub async fn rollback(&self) -> Result<(), ::sqlx::Error> {
+        match self.transaction.lock().await.take() {
+            Some(transaction) => transaction.rollback().await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl<DB: Database> AsyncInjectable for DbTransaction<DB> {
+    async fn inject<T: AsyncServiceHandler>(handler: &T) -> Result<Self, Error> {
+        Ok(Self {
+            pool: handler.try_get_async_service().await?,
+            transaction: Mutex::new(None),
+        })
+    }
+}
+
+impl<DB: Database> AsyncDispose for DbTransaction<DB> {
+    fn dispose(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            if let Err(_error) = self.rollback().await {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %_error, "Transaction of a closed scope couldn't be rolled back");
+            }
+        })
+    }
+}
+
+/// Commits or rolls back the transaction as part of a `UnitOfWork`
+impl<DB: Database> Participant for DbTransaction<DB> {
+    fn commit(&self) -> BoxFuture<'_, Result<(), Box<dyn StdError + Send + Sync>>> {
+        Box::pin(async move { Ok(DbTransaction::commit(self).await?) })
+    }
+
+    fn rollback(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            if let Err(_error) = DbTransaction::rollback(self).await {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %_error, "Transaction couldn't be rolled back");
+            }
+        })
+    }
+}
+
+impl ServiceCollectionBuilder {
+    /// Registers a pool connecting to `url` as an async singleton, e.g. `PgPool` with `add_pool::<Postgres>`
+    /// or `SqlitePool` with `add_pool::<Sqlite>`. The connection is made on first resolution
+    pub fn add_pool<DB: Database>(
+        self,
+        options: PoolOptions<DB>,
+        url: impl Into<Arc<str>>,
+    ) -> Self {
+        self.add_async_service::<Pool<DB>, _>(
+            ServiceType::Singleton,
+            PoolInitializer {
+                options,
+                url: url.into(),
+            },
+        )
+    }
+
+    /// Registers `DbTransaction<DB>` as an async scoped service using the pool registered with `add_pool`
+    pub fn add_transaction<DB: Database>(self) -> Self {
+        self.add_async_scoped::<DbTransaction<DB>>()
+            .async_dispose::<DbTransaction<DB>>()
+    }
+}
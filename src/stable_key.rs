@@ -0,0 +1,51 @@
+use crate::type_map::TypeMap;
+use crate::{
+    Error, ServiceCollection, ServiceCollectionBuilder, ServiceHandler, ServiceInformation,
+    ServiceScope,
+};
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+impl ServiceCollectionBuilder {
+    /// Assigns `key`, e.g. "acme.mailer.v1", to the last registered service.
+    /// Unlike `TypeId`, which differs between binaries and dynamically loaded libraries,
+    /// the key stays the same, so plugins and serialized descriptions of the container can refer to it.
+    /// Keys have to be unique, the service registered first wins otherwise
+    pub fn stable_key(mut self, key: &'static str) -> Self {
+        self.last_registration().stable_key = Some(key);
+        self
+    }
+}
+
+fn type_id_by_key(services: &TypeMap<ServiceInformation>, key: &str) -> Result<TypeId, Error> {
+    services
+        .iter()
+        .find(|(_, information)| information.stable_key == Some(key))
+        .map(|(type_id, _)| *type_id)
+        .ok_or_else(|| Error::UnknownKey(key.to_owned()))
+}
+
+impl ServiceCollection {
+    /// `TypeId` of the service registered with `stable_key`
+    pub fn type_id_by_key(&self, key: &str) -> Result<TypeId, Error> {
+        type_id_by_key(&self.service_info.snapshot(), key)
+    }
+
+    /// The key assigned to the service with `stable_key`, if any
+    pub fn stable_key_of(&self, type_id: &TypeId) -> Option<&'static str> {
+        self.service_info.snapshot().get(type_id)?.stable_key
+    }
+
+    /// Resolves the service registered with `stable_key` like `try_get_service_by_type_id`
+    pub fn get_service_by_key(&self, key: &str) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        self.try_get_service_by_type_id(&self.type_id_by_key(key)?)
+    }
+}
+
+impl ServiceScope {
+    /// Resolves the service registered with `stable_key` like `try_get_service_by_type_id`
+    pub fn get_service_by_key(&self, key: &str) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let type_id = type_id_by_key(&self.services.snapshot(), key)?;
+        self.try_get_service_by_type_id(&type_id)
+    }
+}
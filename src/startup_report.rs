@@ -0,0 +1,104 @@
+use crate::validation::{find_problems, Checks};
+use crate::{SecretsProvider, ServiceCollection, ServiceType};
+use std::any::TypeId;
+use std::fmt::{Display, Formatter};
+
+/// Summary of a container meant to be logged once at startup, see `ServiceCollection::startup_report`.
+/// `Display` renders it as a few human-readable lines
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StartupReport {
+    pub services: usize,
+    pub singletons: usize,
+    /// Singletons already initialized when the report was made, e.g. by `BuildOptions::eager_singletons`
+    pub initialized_singletons: usize,
+    pub scoped: usize,
+    pub transient: usize,
+    /// Services with an async initializer
+    pub asynchronous: usize,
+    /// Registered sources of configuration and secrets, by type name
+    pub configuration_sources: Vec<&'static str>,
+    /// Problems found by validating the declared dependencies and deprecated registrations
+    pub warnings: Vec<String>,
+}
+
+impl Display for StartupReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Container with {} services", self.services)?;
+        writeln!(
+            f,
+            "  singletons: {} ({} initialized)",
+            self.singletons, self.initialized_singletons
+        )?;
+        writeln!(f, "  scoped: {}", self.scoped)?;
+        writeln!(f, "  transient: {}", self.transient)?;
+        writeln!(f, "  async: {}", self.asynchronous)?;
+        if self.configuration_sources.is_empty() {
+            writeln!(f, "  configuration sources: none")?;
+        } else {
+            writeln!(
+                f,
+                "  configuration sources: {}",
+                self.configuration_sources.join(", ")
+            )?;
+        }
+        write!(f, "  warnings: {}", self.warnings.len())?;
+        for warning in &self.warnings {
+            write!(f, "\n    {warning}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ServiceCollection {
+    /// Summarizes the registrations, initialized singletons, configuration sources
+    /// and problems of the container, in registration order
+    pub fn startup_report(&self) -> StartupReport {
+        let services = self.service_info.snapshot();
+        let initialized = self.singletons.snapshot();
+        let sources = [
+            #[cfg(feature = "figment")]
+            TypeId::of::<::figment::Figment>(),
+            TypeId::of::<dyn SecretsProvider>(),
+        ];
+        let checks = Checks {
+            graph: true,
+            lifetimes: true,
+        };
+
+        let mut report = StartupReport::default();
+        for (type_id, information) in services.iter() {
+            report.services += 1;
+            match information.type_ {
+                ServiceType::Singleton => {
+                    report.singletons += 1;
+                    if initialized.contains_key(type_id) {
+                        report.initialized_singletons += 1;
+                    }
+                }
+                ServiceType::Scoped => report.scoped += 1,
+                ServiceType::Transient => report.transient += 1,
+            }
+            if information.initialize_async_fn.is_some() {
+                report.asynchronous += 1;
+            }
+            if sources.contains(type_id) {
+                report.configuration_sources.push(information.type_name);
+            }
+
+            if let Some(message) = information.deprecated {
+                report.warnings.push(format!(
+                    "Service {} is deprecated: {message}",
+                    information.type_name
+                ));
+            }
+            for problem in find_problems(&services, [type_id], checks) {
+                let warning = problem.to_string();
+                if !report.warnings.contains(&warning) {
+                    report.warnings.push(warning);
+                }
+            }
+        }
+
+        report
+    }
+}
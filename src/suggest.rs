@@ -0,0 +1,76 @@
+use crate::Error;
+
+/// `crate::module::Name<Generic>` and `dyn crate::Name` both become `name`
+fn short_name(type_name: &str) -> String {
+    let without_generics = type_name.split('<').next().unwrap_or(type_name);
+    let without_dyn = without_generics.trim_start_matches("dyn ");
+    without_dyn
+        .rsplit("::")
+        .next()
+        .unwrap_or(without_dyn)
+        .to_lowercase()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Registered type names that look like a typo or a near miss of the requested one,
+/// e.g. `SmtpMailer` for `Mailer` or `app::Config` for `other::Config`
+pub(crate) fn similar_type_names(
+    requested: &str,
+    registered: impl IntoIterator<Item = &'static str>,
+) -> Vec<&'static str> {
+    let requested_short = short_name(requested);
+    let mut similar: Vec<_> = registered
+        .into_iter()
+        .filter(|name| *name != requested)
+        .filter(|name| {
+            let short = short_name(name);
+            short.contains(&requested_short)
+                || requested_short.contains(&short)
+                || edit_distance(&short, &requested_short) <= requested_short.len().max(3) / 3
+        })
+        .collect();
+    similar.sort_unstable();
+    similar
+}
+
+pub(crate) fn missing_service_message(
+    requested: &'static str,
+    registered: impl IntoIterator<Item = &'static str>,
+) -> String {
+    let similar = similar_type_names(requested, registered);
+    if similar.is_empty() {
+        format!("Service {requested} couldn't be found")
+    } else {
+        format!(
+            "Service {requested} couldn't be found. Did you mean {}?",
+            similar.join(", ")
+        )
+    }
+}
+
+/// Panic message of a required service that couldn't be resolved
+pub(crate) fn failed_service_message(
+    requested: &'static str,
+    error: &Error,
+    registered: impl IntoIterator<Item = &'static str>,
+) -> String {
+    match error {
+        Error::NotRegistered {
+            requested_by: None, ..
+        } => missing_service_message(requested, registered),
+        error => format!("Service {requested} couldn't be resolved: {error}"),
+    }
+}
@@ -0,0 +1,99 @@
+//! Readiness and watchdog notifications for services managed by systemd, see `sd_notify(3)`.
+//! `initialize_and_notify_ready` signals readiness once the container is initialized
+//! and `run_watchdog` pings the watchdog while the registered health checks pass
+
+use crate::{Error, ServiceCollection, ServiceHandler};
+use std::io;
+use std::os::unix::net::UnixDatagram;
+use std::time::Duration;
+
+/// Sends `state`, e.g. `"STATUS=Warming caches"`, to the socket in `NOTIFY_SOCKET`.
+/// Returns `Ok(false)` if the process isn't run by systemd
+pub fn notify(state: &str) -> io::Result<bool> {
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return Ok(false);
+    };
+
+    let socket = UnixDatagram::unbound()?;
+    match path.to_str().and_then(|p| p.strip_prefix('@')) {
+        #[cfg(target_os = "linux")]
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+            let address = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            socket.send_to_addr(state.as_bytes(), &address)?;
+        }
+        _ => {
+            socket.send_to(state.as_bytes(), path)?;
+        }
+    }
+    Ok(true)
+}
+
+/// Signals that startup finished, `initialize_and_notify_ready` sends it after initializing the singletons
+pub fn notify_ready() -> io::Result<bool> {
+    notify("READY=1")
+}
+
+pub fn notify_stopping() -> io::Result<bool> {
+    notify("STOPPING=1")
+}
+
+/// Resets the watchdog timer, should be sent every `watchdog_interval() / 2` while the service is healthy
+pub fn notify_watchdog() -> io::Result<bool> {
+    notify("WATCHDOG=1")
+}
+
+/// Interval configured through `WatchdogSec=`, `None` if the watchdog isn't enabled for this process
+pub fn watchdog_interval() -> Option<Duration> {
+    if let Ok(pid) = std::env::var("WATCHDOG_PID") {
+        if pid.parse() != Ok(std::process::id()) {
+            return None;
+        }
+    }
+
+    let micros = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    Some(Duration::from_micros(micros))
+}
+
+/// Check of a service's health, every registration of `dyn HealthCheck` has to pass for `run_watchdog` to ping,
+/// e.g. through `#[injectable(implements(dyn HealthCheck))]`
+pub trait HealthCheck: Send + Sync {
+    fn is_healthy(&self) -> bool;
+}
+
+/// A failed notification is only logged, systemd notices the missing notification itself
+fn log_failure(result: io::Result<bool>) {
+    if let Err(_error) = result {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(error = %_error, "Couldn't notify systemd");
+    }
+}
+
+impl ServiceCollection {
+    /// Awaits `initialize_async_singletons`, then sends `READY=1`.
+    /// Build the collection with `BuildOptions::eager_singletons` to initialize the synchronous singletons before
+    pub async fn initialize_and_notify_ready(&self) -> Result<(), Error> {
+        self.initialize_async_singletons().await?;
+        log_failure(notify_ready());
+        Ok(())
+    }
+
+    /// Sends `WATCHDOG=1` every half `watchdog_interval()` while every registered `dyn HealthCheck` is healthy,
+    /// until the container's cancellation token is triggered. Returns right away if the watchdog isn't enabled
+    pub async fn run_watchdog(&self) {
+        let Some(interval) = watchdog_interval() else {
+            return;
+        };
+        let token = self.cancellation_token().clone();
+        loop {
+            let checks = self.get_dyn_services::<dyn HealthCheck>();
+            if checks.iter().all(|check| check.is_healthy()) {
+                log_failure(notify_watchdog());
+            }
+            let pause = tokio::time::sleep(interval / 2);
+            if token.run_until_cancelled(pause).await.is_none() {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Command scoped services for Tauri, e.g. `tauri::Builder::default().manage_services(collection)`
+//! and `.invoke_handler(scoped_handler(tauri::generate_handler![...]))` with commands taking `Inject<Dep<UserService>>`
+
+use crate::{FromScope, ServiceCollection, ServiceHandler, ServiceScope};
+use ::tauri::ipc::{CommandArg, CommandItem, Invoke, InvokeError};
+use ::tauri::{Builder, Manager, Runtime};
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+thread_local! {
+    /// Scope of the invocation `scoped_handler` is currently extracting the arguments of
+    static CURRENT: RefCell<Option<Arc<CommandScope>>> = const { RefCell::new(None) };
+}
+
+/// Scope shared by the `Inject` arguments of one invocation, closed once the last of them is dropped
+struct CommandScope(ServiceScope);
+
+impl Drop for CommandScope {
+    fn drop(&mut self) {
+        ::tauri::async_runtime::spawn(self.0.clone().close());
+    }
+}
+
+/// Registers the collection as managed state so `Inject` can resolve from it
+pub trait ManageServices {
+    fn manage_services(self, collection: ServiceCollection) -> Self;
+}
+
+impl<R: Runtime> ManageServices for Builder<R> {
+    fn manage_services(self, collection: ServiceCollection) -> Self {
+        self.manage(collection)
+    }
+}
+
+/// Creates a scope for every invocation of `handler`, e.g. `scoped_handler(tauri::generate_handler![...])`,
+/// which the `Inject` arguments of the invocation resolve from.
+/// The scope is closed once the command returned, or its future completed for async commands
+pub fn scoped_handler<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let scope = invoke
+            .message
+            .webview_ref()
+            .try_state::<ServiceCollection>()
+            .map(|collection| Arc::new(CommandScope(collection.create_scope())));
+        // The arguments are extracted before `handler` returns, also for async commands
+        let previous = CURRENT.replace(scope);
+        let handled = handler(invoke);
+        CURRENT.set(previous);
+        handled
+    }
+}
+
+/// Command argument resolving `T` from the scope `scoped_handler` created for the invocation.
+/// Use a tuple like `Inject<(Dep<A>, Dep<B>)>` to resolve several services at once,
+/// the invocation is rejected if any of them can't be resolved
+pub struct Inject<T>(pub T, Arc<CommandScope>);
+
+impl<T> Inject<T> {
+    /// The scope is closed once every `Inject` of the invocation was dropped,
+    /// so the services are disposed while the returned value might still be in use
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Inject<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Inject<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'de, T: FromScope, R: Runtime> CommandArg<'de, R> for Inject<T> {
+    fn from_command(command: CommandItem<'de, R>) -> Result<Self, InvokeError> {
+        let Some(scope) = CURRENT.with_borrow(Option::clone) else {
+            return Err(InvokeError::from(format!(
+                "No scope for argument `{}` of command `{}`, call `manage_services` and wrap the invoke handler with `scoped_handler`",
+                command.key, command.name
+            )));
+        };
+
+        T::from_scope(&scope.0)
+            .map(|value| Inject(value, scope.clone()))
+            .map_err(InvokeError::from_error)
+    }
+}
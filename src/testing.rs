@@ -0,0 +1,217 @@
+//! Utilities for covering container wiring with unit tests
+
+use crate::{
+    BoxFuture, Clock, Dep, Initialize, ServiceCollection, ServiceCollectionBuilder, ServiceHandler,
+    ServiceType,
+};
+use std::any::{Any, TypeId};
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// A single resolution captured by a `TestCollection`
+#[derive(Debug, Clone)]
+pub struct ResolutionRecord {
+    pub type_id: TypeId,
+    /// `None` if the service isn't registered in the container
+    pub type_name: Option<&'static str>,
+    pub resolved: bool,
+}
+
+type ResolutionLog = Arc<Mutex<Vec<ResolutionRecord>>>;
+
+/// Wraps a `ServiceCollectionBuilder` so registrations can be overridden for tests
+pub struct TestCollectionBuilder {
+    builder: ServiceCollectionBuilder,
+}
+
+impl TestCollectionBuilder {
+    pub fn new(builder: ServiceCollectionBuilder) -> Self {
+        Self { builder }
+    }
+
+    /// Replaces the registration of `T`, keeping its lifetime.
+    /// Panics if `T` isn't registered, as that usually means the override is wired to the wrong type
+    #[track_caller]
+    pub fn override_with<T, I>(self, initializer: I) -> Self
+    where
+        T: Any + Send + Sync,
+        I: Initialize<T> + Clone + Send + Sync + 'static,
+    {
+        let type_ = match self.builder.services.get(&TypeId::of::<T>()) {
+            Some(information) => information.type_.clone(),
+            None => panic!(
+                "Cannot override {} as it isn't registered",
+                std::any::type_name::<T>()
+            ),
+        };
+
+        Self {
+            builder: self.builder.add_service::<T, I>(type_, initializer),
+        }
+    }
+
+    /// Registers `clock` as the `dyn Clock` of the collection
+    pub fn use_clock(self, clock: &TestClock) -> Self {
+        Self {
+            builder: self
+                .builder
+                .add_service_as::<dyn Clock, _>(ServiceType::Singleton, clock.clone()),
+        }
+    }
+
+    /// Builds the collection, capturing every synchronous resolution made through it
+    pub fn build(self) -> TestCollection {
+        let log = ResolutionLog::default();
+        let resolver_log = log.clone();
+
+        let collection = self
+            .builder
+            .use_resolver(move |context, next| {
+                let value = next.run();
+                resolver_log
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push(ResolutionRecord {
+                        type_id: *context.type_id,
+                        type_name: context.type_name,
+                        resolved: value.is_ok(),
+                    });
+                value
+            })
+            .build();
+
+        TestCollection { collection, log }
+    }
+}
+
+/// A `ServiceCollection` with assertions on its wiring
+pub struct TestCollection {
+    collection: ServiceCollection,
+    log: ResolutionLog,
+}
+
+impl TestCollection {
+    /// Resolves `T`, panicking with the type name if that isn't possible
+    #[track_caller]
+    pub fn assert_resolvable<T: Any + Send + Sync>(&self) -> Dep<T> {
+        match self.collection.get_service::<T>() {
+            Some(s) => s,
+            None => panic!("{} isn't resolvable", std::any::type_name::<T>()),
+        }
+    }
+
+    /// Panics if `T` isn't registered with the given lifetime
+    #[track_caller]
+    pub fn assert_lifetime<T: Any + Send + Sync>(&self, type_: ServiceType) {
+        let services = self.collection.service_info.snapshot();
+        match services.get(&TypeId::of::<T>()) {
+            Some(information) => assert_eq!(
+                information.type_, type_,
+                "{} is registered with the wrong lifetime",
+                information.type_name
+            ),
+            None => panic!("{} isn't registered", std::any::type_name::<T>()),
+        }
+    }
+
+    /// Every resolution made so far, including the dependencies resolved along the way
+    pub fn resolution_log(&self) -> Vec<ResolutionRecord> {
+        self.log
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn clear_resolution_log(&self) {
+        self.log
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+impl Deref for TestCollection {
+    type Target = ServiceCollection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.collection
+    }
+}
+
+/// `Clock` that only moves when `advance` is called.
+/// Clones share their time, so a test can keep one to control the clock it registered
+#[derive(Clone)]
+pub struct TestClock {
+    start: Instant,
+    elapsed: Arc<watch::Sender<Duration>>,
+}
+
+impl TestClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Arc::new(watch::Sender::new(Duration::ZERO)),
+        }
+    }
+
+    /// Moves the clock forward, completing the sleeps that end within `duration`
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed.send_modify(|elapsed| *elapsed += duration);
+    }
+
+    /// Time advanced since the clock was created
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.borrow()
+    }
+}
+
+impl Default for TestClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'_, ()> {
+        let deadline = self.elapsed() + duration;
+        let mut elapsed = self.elapsed.subscribe();
+        Box::pin(async move {
+            // The sender lives as long as `self`, so waiting can't fail
+            let _ = elapsed.wait_for(|elapsed| *elapsed >= deadline).await;
+        })
+    }
+}
+
+impl Initialize<Arc<dyn Clock>> for TestClock {
+    fn initialize<H: ServiceHandler>(&self, _handler: &H) -> Arc<dyn Clock> {
+        Arc::new(self.clone())
+    }
+}
+
+/// Asserts that every dependency declared by a service's `Injectable` implementation
+/// is registered in the given `ServiceCollectionBuilder` or `ServiceCollection`
+#[macro_export]
+macro_rules! assert_dependencies {
+    ($container:expr, $service:ty) => {{
+        let container = &$container;
+        let missing: ::std::vec::Vec<&'static str> =
+            <$service as $crate::Injectable>::dependencies()
+                .into_iter()
+                .filter(|d| !container.is_registered(&d.type_id))
+                .map(|d| d.type_name)
+                .collect();
+
+        assert!(
+            missing.is_empty(),
+            "{} depends on services that aren't registered: {}",
+            ::std::stringify!($service),
+            missing.join(", ")
+        );
+    }};
+}
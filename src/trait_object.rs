@@ -0,0 +1,299 @@
+use crate::type_map::TypeMap;
+use crate::{
+    Dep, Dependency, Initialize, Injectable, RegistrationData, RegistrationSummary,
+    ServiceCollection, ServiceCollectionBuilder, ServiceHandler, ServiceInformation, ServiceScope,
+    ServiceType,
+};
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+/// How trait objects are stored in the container.
+/// Keeps the concrete service around so it can be resolved through `get_implementation`
+pub(crate) struct TraitObject<T: ?Sized> {
+    pub(crate) object: Arc<T>,
+    /// `None` for custom initializers registered through `add_service_as`
+    implementation: Option<Arc<dyn Any + Send + Sync>>,
+}
+
+impl<T: ?Sized> TraitObject<T> {
+    /// Trait object created by a custom initializer, without a known implementation
+    pub(crate) fn from_object(object: Arc<T>) -> Self {
+        Self {
+            object,
+            implementation: None,
+        }
+    }
+
+    /// Trait object converted from the concrete service `implementation` with `cast`
+    pub(crate) fn cast<C: Any + Send + Sync>(
+        implementation: Arc<C>,
+        cast: fn(Arc<C>) -> Arc<T>,
+    ) -> Self {
+        Self {
+            object: cast(implementation.clone()),
+            implementation: Some(implementation),
+        }
+    }
+}
+
+/// Concrete service behind a trait object registration
+#[derive(Clone)]
+pub(crate) struct Implementation {
+    pub(crate) dependency: Dependency,
+    /// Whether the trait object shares the registered instance of the implementation,
+    /// as with `#[injectable(implements(...))]`, instead of creating its own
+    pub(crate) shared: bool,
+    /// Extracts the concrete service out of the stored `TraitObject`
+    extract: fn(&(dyn Any + Send + Sync)) -> Option<Arc<dyn Any + Send + Sync>>,
+}
+
+impl Implementation {
+    /// The concrete service of a stored `TraitObject`, `None` if it isn't one of this registration
+    pub(crate) fn extract(
+        &self,
+        value: &(dyn Any + Send + Sync),
+    ) -> Option<Arc<dyn Any + Send + Sync>> {
+        (self.extract)(value)
+    }
+
+    pub(crate) fn of<T: ?Sized + Send + Sync + 'static, C: Any>() -> Self {
+        Self {
+            dependency: Dependency::of::<C>(),
+            shared: false,
+            extract: |value| {
+                value
+                    .downcast_ref::<TraitObject<T>>()?
+                    .implementation
+                    .clone()
+            },
+        }
+    }
+}
+
+/// Injects `C` and converts it into the trait object `T`
+struct CastInitializer<C, T: ?Sized> {
+    cast: fn(Arc<C>) -> Arc<T>,
+}
+
+impl<C, T: ?Sized> Clone for CastInitializer<C, T> {
+    fn clone(&self) -> Self {
+        Self { cast: self.cast }
+    }
+}
+
+impl<C: Injectable + Any + Send + Sync, T: ?Sized + Send + Sync + 'static>
+    Initialize<TraitObject<T>> for CastInitializer<C, T>
+{
+    fn initialize<H: ServiceHandler>(&self, handler: &H) -> TraitObject<T> {
+        TraitObject::cast(Arc::new(C::inject(handler)), self.cast)
+    }
+}
+
+/// Resolves the registered `C` and converts it into the trait object `T`,
+/// so the trait object shares the instance of `C`
+struct BindingInitializer<C, T: ?Sized> {
+    cast: fn(Arc<C>) -> Arc<T>,
+}
+
+impl<C, T: ?Sized> Clone for BindingInitializer<C, T> {
+    fn clone(&self) -> Self {
+        Self { cast: self.cast }
+    }
+}
+
+impl<C: Any + Send + Sync, T: ?Sized + Send + Sync + 'static> Initialize<TraitObject<T>>
+    for BindingInitializer<C, T>
+{
+    fn initialize<H: ServiceHandler>(&self, handler: &H) -> TraitObject<T> {
+        TraitObject::cast(handler.get_required_service::<C>().0, self.cast)
+    }
+}
+
+/// Adapts a custom initializer of `Arc<T>` to the storage of trait objects
+#[derive(Clone)]
+struct ObjectInitializer<I>(I);
+
+impl<T: ?Sized + Send + Sync + 'static, I: Initialize<Arc<T>>> Initialize<TraitObject<T>>
+    for ObjectInitializer<I>
+{
+    fn initialize<H: ServiceHandler>(&self, handler: &H) -> TraitObject<T> {
+        TraitObject::from_object(self.0.initialize(handler))
+    }
+
+    fn initialize_with_data<H: ServiceHandler>(
+        &self,
+        handler: &H,
+        data: &RegistrationData,
+    ) -> TraitObject<T> {
+        TraitObject::from_object(self.0.initialize_with_data(handler, data))
+    }
+}
+
+pub(crate) fn trait_object_information<T, I>(
+    type_: ServiceType,
+    initializer: I,
+) -> ServiceInformation
+where
+    T: ?Sized + Send + Sync + 'static,
+    I: Initialize<TraitObject<T>> + Clone + Send + Sync + 'static,
+{
+    let mut information = ServiceInformation::new::<TraitObject<T>, I>(type_, initializer);
+    information.type_name = std::any::type_name::<T>();
+    information
+}
+
+/// Trait object a service is also registered as, emitted by `#[injectable(implements(...))]`
+pub struct Binding {
+    type_id: TypeId,
+    information: Box<dyn Fn(ServiceType) -> ServiceInformation + Send + Sync>,
+}
+
+impl Binding {
+    /// Binds the trait object `T` to the service `C`, `cast` is usually `|c| c`
+    pub fn new<T, C>(cast: fn(Arc<C>) -> Arc<T>) -> Self
+    where
+        T: ?Sized + Send + Sync + 'static,
+        C: Any + Send + Sync,
+    {
+        Self {
+            type_id: TypeId::of::<T>(),
+            information: Box::new(move |type_| {
+                let mut information =
+                    trait_object_information::<T, _>(type_, BindingInitializer { cast });
+                information.dependencies = vec![Dependency::of::<C>()];
+                information.implementation = Some(Implementation {
+                    shared: true,
+                    ..Implementation::of::<T, C>()
+                });
+                information
+            }),
+        }
+    }
+}
+
+impl ServiceCollectionBuilder {
+    /// Registers the trait objects `T` declares through `Injectable::bindings` with the lifetime of `T`
+    pub(crate) fn add_bindings<T: Injectable>(mut self, type_: ServiceType) -> Self {
+        for binding in T::bindings() {
+            self.register(binding.type_id, (binding.information)(type_.clone()));
+        }
+        self
+    }
+
+    /// Registers a trait object, e.g. `dyn Mailer`, that is resolved as `Dep<dyn Mailer>`
+    /// through `get_dyn_service` or a `Dep<dyn Mailer>` field of a derived `Injectable`.
+    /// The trait has to be `Send + Sync`
+    pub fn add_service_as<
+        T: ?Sized + Send + Sync + 'static,
+        I: Initialize<Arc<T>> + Clone + Send + Sync + 'static,
+    >(
+        mut self,
+        type_: ServiceType,
+        initializer: I,
+    ) -> Self {
+        let information = trait_object_information::<T, _>(type_, ObjectInitializer(initializer));
+        self.register(TypeId::of::<T>(), information);
+        self.last_registered = Some(TypeId::of::<T>());
+
+        self
+    }
+
+    fn add_cast<T, C>(mut self, type_: ServiceType, cast: fn(Arc<C>) -> Arc<T>) -> Self
+    where
+        T: ?Sized + Send + Sync + 'static,
+        C: Injectable + Any + Send + Sync,
+    {
+        let mut information = trait_object_information::<T, _>(type_, CastInitializer { cast });
+        information.dependencies = C::dependencies();
+        information.implementation = Some(Implementation::of::<T, C>());
+
+        self.register(TypeId::of::<T>(), information);
+        self.last_registered = Some(TypeId::of::<T>());
+
+        self
+    }
+
+    /// Registers `C` as the singleton implementation of the trait object `T`.
+    /// `cast` converts the service into the trait object, usually `|c| c`
+    pub fn add_singleton_as<T, C>(self, cast: fn(Arc<C>) -> Arc<T>) -> Self
+    where
+        T: ?Sized + Send + Sync + 'static,
+        C: Injectable + Any + Send + Sync,
+    {
+        self.add_cast::<T, C>(ServiceType::Singleton, cast)
+    }
+
+    /// Registers `C` as the scoped implementation of the trait object `T`.
+    /// `cast` converts the service into the trait object, usually `|c| c`
+    pub fn add_scoped_as<T, C>(self, cast: fn(Arc<C>) -> Arc<T>) -> Self
+    where
+        T: ?Sized + Send + Sync + 'static,
+        C: Injectable + Any + Send + Sync,
+    {
+        self.add_cast::<T, C>(ServiceType::Scoped, cast)
+    }
+
+    /// Registers `C` as the transient implementation of the trait object `T`.
+    /// `cast` converts the service into the trait object, usually `|c| c`
+    pub fn add_transient_as<T, C>(self, cast: fn(Arc<C>) -> Arc<T>) -> Self
+    where
+        T: ?Sized + Send + Sync + 'static,
+        C: Injectable + Any + Send + Sync,
+    {
+        self.add_cast::<T, C>(ServiceType::Transient, cast)
+    }
+}
+
+/// Resolves `C` directly, or through the first trait object registration implemented by `C`
+fn get_implementation<C: Any + Send + Sync, H: ServiceHandler>(
+    services: &TypeMap<ServiceInformation>,
+    handler: &H,
+) -> Option<Dep<C>> {
+    if let Some(service) = handler.get_service::<C>() {
+        return Some(service);
+    }
+
+    services.iter().find_map(|(type_id, information)| {
+        let implementation = information.implementation.as_ref()?;
+        if implementation.dependency.type_id != TypeId::of::<C>() {
+            return None;
+        }
+
+        let value = handler.get_service_by_type_id(type_id)?;
+        Some(Dep((implementation.extract)(value.as_ref())?
+            .downcast::<C>()
+            .ok()?))
+    })
+}
+
+impl ServiceCollection {
+    /// Resolves the concrete `C`, even if it's only registered as a trait object through `add_*_as`.
+    /// Meant for tests and diagnostics asserting on implementation specific state
+    pub fn get_implementation<C: Any + Send + Sync>(&self) -> Option<Dep<C>> {
+        get_implementation(&self.service_info.snapshot(), self)
+    }
+
+    /// Describes the registration of `T`, including the implementation behind a trait object
+    pub fn descriptor_for<T: ?Sized + 'static>(&self) -> Option<RegistrationSummary> {
+        let services = self.service_info.snapshot();
+        services
+            .get(&TypeId::of::<T>())
+            .map(RegistrationSummary::from)
+    }
+}
+
+impl ServiceScope {
+    /// Resolves the concrete `C`, even if it's only registered as a trait object through `add_*_as`.
+    /// Meant for tests and diagnostics asserting on implementation specific state
+    pub fn get_implementation<C: Any + Send + Sync>(&self) -> Option<Dep<C>> {
+        get_implementation(&self.services.snapshot(), self)
+    }
+
+    /// Describes the registration of `T`, including the implementation behind a trait object
+    pub fn descriptor_for<T: ?Sized + 'static>(&self) -> Option<RegistrationSummary> {
+        let services = self.services.snapshot();
+        services
+            .get(&TypeId::of::<T>())
+            .map(RegistrationSummary::from)
+    }
+}
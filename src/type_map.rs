@@ -0,0 +1,70 @@
+use indexmap::IndexMap;
+use smallvec::SmallVec;
+use std::any::TypeId;
+
+/// Number of entries stored inline before switching to an `IndexMap`
+const INLINE_CAPACITY: usize = 8;
+
+/// Map keyed by `TypeId` that stores few entries inline and looks them up linearly,
+/// which is both smaller and faster than hashing for the small containers of CLIs and tests.
+/// Both representations iterate in insertion order, replacing a value keeps its position
+#[derive(Clone)]
+pub(crate) enum TypeMap<V> {
+    Inline(SmallVec<[(TypeId, V); INLINE_CAPACITY]>),
+    Hashed(IndexMap<TypeId, V>),
+}
+
+impl<V> Default for TypeMap<V> {
+    fn default() -> Self {
+        Self::Inline(SmallVec::new())
+    }
+}
+
+impl<V> TypeMap<V> {
+    pub(crate) fn get(&self, type_id: &TypeId) -> Option<&V> {
+        match self {
+            Self::Inline(entries) => entries.iter().find(|(t, _)| t == type_id).map(|(_, v)| v),
+            Self::Hashed(map) => map.get(type_id),
+        }
+    }
+
+    pub(crate) fn contains_key(&self, type_id: &TypeId) -> bool {
+        self.get(type_id).is_some()
+    }
+
+    pub(crate) fn insert(&mut self, type_id: TypeId, value: V) {
+        match self {
+            Self::Inline(entries) => {
+                if let Some((_, v)) = entries.iter_mut().find(|(t, _)| *t == type_id) {
+                    *v = value;
+                } else if entries.len() < INLINE_CAPACITY {
+                    entries.push((type_id, value));
+                } else {
+                    let mut map: IndexMap<TypeId, V> = entries.drain(..).collect();
+                    map.insert(type_id, value);
+                    *self = Self::Hashed(map);
+                }
+            }
+            Self::Hashed(map) => {
+                map.insert(type_id, value);
+            }
+        }
+    }
+
+    pub(crate) fn iter(&self) -> Box<dyn Iterator<Item = (&TypeId, &V)> + '_> {
+        match self {
+            Self::Inline(entries) => Box::new(entries.iter().map(|(t, v)| (t, v))),
+            Self::Hashed(map) => Box::new(map.iter()),
+        }
+    }
+}
+
+impl<V> FromIterator<(TypeId, V)> for TypeMap<V> {
+    fn from_iter<T: IntoIterator<Item = (TypeId, V)>>(iter: T) -> Self {
+        let mut map = Self::default();
+        for (type_id, value) in iter {
+            map.insert(type_id, value);
+        }
+        map
+    }
+}
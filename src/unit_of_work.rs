@@ -0,0 +1,116 @@
+use crate::{
+    AsyncDispose, BoxFuture, Dep, Error, Injectable, ServiceCollection, ServiceCollectionBuilder,
+    ServiceHandler, ServiceScope,
+};
+use std::error::Error as StdError;
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Work that is committed or rolled back together with the other participants of a `UnitOfWork`,
+/// e.g. a database transaction or buffered messages
+pub trait Participant: Send + Sync {
+    fn commit(&self) -> BoxFuture<'_, Result<(), Box<dyn StdError + Send + Sync>>>;
+
+    fn rollback(&self) -> BoxFuture<'_, ()>;
+}
+
+/// Scoped service collecting the participants of a scope, registered with `add_unit_of_work`.
+/// It's only committed by `commit`, e.g. through `ServiceCollection::run_unit_of_work`,
+/// `ServiceScope::close` rolls back the participants that weren't committed
+#[derive(Default)]
+pub struct UnitOfWork {
+    participants: Mutex<Vec<Arc<dyn Participant>>>,
+}
+
+impl UnitOfWork {
+    /// Adds a resolved participant, which is committed or rolled back after the ones enlisted before it
+    pub fn enlist<P: Participant + 'static>(&self, participant: Dep<P>) {
+        self.participants
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(participant.0);
+    }
+
+    fn take(&self) -> Vec<Arc<dyn Participant>> {
+        std::mem::take(
+            &mut self
+                .participants
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        )
+    }
+
+    /// Commits the participants in the order they were enlisted.
+    /// If one fails, the remaining ones are rolled back and `Error::CommitFailed` is returned
+    pub async fn commit(&self) -> Result<(), Error> {
+        let mut participants = self.take().into_iter();
+        while let Some(participant) = participants.next() {
+            if let Err(source) = participant.commit().await {
+                for participant in participants {
+                    participant.rollback().await;
+                }
+                return Err(Error::CommitFailed { source });
+            }
+        }
+        Ok(())
+    }
+
+    /// Rolls back the participants in the order they were enlisted
+    pub async fn rollback(&self) {
+        for participant in self.take() {
+            participant.rollback().await;
+        }
+    }
+}
+
+impl Injectable for UnitOfWork {
+    fn inject<T: ServiceHandler>(_handler: &T) -> Self {
+        Self::default()
+    }
+}
+
+impl AsyncDispose for UnitOfWork {
+    fn dispose(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            // Committing or rolling back takes the participants, so this only rolls back unfinished work
+            self.rollback().await;
+        })
+    }
+}
+
+impl ServiceCollectionBuilder {
+    /// Registers `UnitOfWork` as a scoped service
+    pub fn add_unit_of_work(self) -> Self {
+        self.add_scoped::<UnitOfWork>()
+            .async_dispose::<UnitOfWork>()
+    }
+}
+
+impl ServiceCollection {
+    /// Awaits the future returned by `f` with a new scope. If the future succeeds,
+    /// the scope's `UnitOfWork` is committed and the scope is closed afterwards.
+    /// If either fails, the `UnitOfWork` is rolled back and the scope is dropped without being closed,
+    /// so no other `async_dispose` hook acts on the failed work.
+    /// Fails without calling `f` if `UnitOfWork` isn't registered
+    pub async fn run_unit_of_work<R, E, F>(&self, f: impl FnOnce(ServiceScope) -> F) -> Result<R, E>
+    where
+        F: Future<Output = Result<R, E>>,
+        E: From<Error>,
+    {
+        let scope = self.create_scope();
+        let unit_of_work = scope.try_get_service::<UnitOfWork>()?;
+        let result = f(scope.clone()).await;
+
+        match result {
+            Ok(value) => {
+                unit_of_work.commit().await?;
+                scope.close().await;
+                Ok(value)
+            }
+            Err(error) => {
+                unit_of_work.rollback().await;
+                Err(error)
+            }
+        }
+    }
+}
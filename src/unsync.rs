@@ -0,0 +1,46 @@
+use crate::{Initialize, Injectable, ServiceCollectionBuilder, ServiceHandler, ServiceType};
+use std::any::Any;
+use std::marker::PhantomData;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Service that is `Send` but not `Sync`, e.g. a wrapper around a non-thread-safe C handle.
+/// Registered with `add_singleton_unsync` and resolved as `Dep<Unsync<T>>`,
+/// every access goes through `lock`
+pub struct Unsync<T>(Mutex<T>);
+
+impl<T> Unsync<T> {
+    pub fn new(value: T) -> Self {
+        Self(Mutex::new(value))
+    }
+
+    /// Waits until no other thread uses the service. A panic while it was locked doesn't poison it
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+struct UnsyncInitializer<T>(PhantomData<fn() -> T>);
+
+impl<T> Clone for UnsyncInitializer<T> {
+    fn clone(&self) -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Injectable + Any + Send> Initialize<Unsync<T>> for UnsyncInitializer<T> {
+    fn initialize<H: ServiceHandler>(&self, handler: &H) -> Unsync<T> {
+        Unsync::new(T::inject(handler))
+    }
+}
+
+impl ServiceCollectionBuilder {
+    /// Registers `T` as a singleton that is resolved as `Dep<Unsync<T>>`
+    pub fn add_singleton_unsync<T: Injectable + Any + Send>(self) -> Self {
+        let mut builder = self.add_service::<Unsync<T>, UnsyncInitializer<T>>(
+            ServiceType::Singleton,
+            UnsyncInitializer(PhantomData),
+        );
+        builder.last_registration().dependencies = T::dependencies();
+        builder
+    }
+}
@@ -0,0 +1,98 @@
+use crate::deprecation;
+use crate::type_map::TypeMap;
+use crate::{Registry, ServiceCollection, ServiceCollectionBuilder, ServiceScope};
+use arc_swap::ArcSwap;
+use std::any::TypeId;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Registrations that have been resolved at least once, shared between the collection and its scopes.
+/// Marking an already resolved service is a wait-free load, like `ServiceCache`
+pub(crate) struct Usage {
+    resolved: ArcSwap<TypeMap<()>>,
+    write_lock: Mutex<()>,
+    registry: Registry,
+    /// Set through `warn_unused_on_drop`, which needs the `tracing` feature
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    warn_on_drop: bool,
+}
+
+impl Usage {
+    pub(crate) fn new(registry: Registry, warn_on_drop: bool) -> Self {
+        Self {
+            resolved: Default::default(),
+            write_lock: Default::default(),
+            registry,
+            warn_on_drop,
+        }
+    }
+
+    pub(crate) fn mark(&self, type_id: &TypeId) {
+        if self.resolved.load().contains_key(type_id) {
+            return;
+        }
+
+        let _guard = self
+            .write_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let current = self.resolved.load();
+        if current.contains_key(type_id) {
+            return;
+        }
+
+        let mut resolved = TypeMap::clone(&current);
+        resolved.insert(*type_id, ());
+        self.resolved.store(Arc::new(resolved));
+
+        if let Some(information) = self.registry.snapshot().get(type_id) {
+            deprecation::warn_deprecated(information);
+        }
+    }
+
+    fn unused(&self) -> Vec<&'static str> {
+        let resolved = self.resolved.load();
+        let services = self.registry.snapshot();
+        services
+            .iter()
+            .filter(|(type_id, _)| !resolved.contains_key(type_id))
+            .map(|(_, information)| information.type_name)
+            .collect()
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for Usage {
+    fn drop(&mut self) {
+        if self.warn_on_drop {
+            for service in self.unused() {
+                tracing::warn!(service, "Service was registered but never resolved");
+            }
+        }
+    }
+}
+
+impl ServiceCollection {
+    /// Type names of the registrations that haven't been resolved by this collection or any of its scopes,
+    /// in registration order. Helps to find dead wiring and services registered under the wrong type
+    pub fn unused_services(&self) -> Vec<&'static str> {
+        self.usage.unused()
+    }
+}
+
+impl ServiceScope {
+    /// Type names of the registrations that haven't been resolved by the collection or any of its scopes,
+    /// in registration order
+    pub fn unused_services(&self) -> Vec<&'static str> {
+        self.usage.unused()
+    }
+}
+
+impl ServiceCollectionBuilder {
+    /// Logs a warning for every service that was never resolved
+    /// once the collection and all of its scopes are dropped
+    #[cfg(feature = "tracing")]
+    pub fn warn_unused_on_drop(mut self) -> Self {
+        self.warn_unused_on_drop = true;
+        self
+    }
+}
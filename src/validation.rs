@@ -0,0 +1,152 @@
+use crate::type_map::TypeMap;
+use crate::{
+    CancellationToken, Clock, Error, ScopeId, ServiceCollection, ServiceInformation, ServiceScope,
+    ServiceType, WeakProvider,
+};
+use std::any::TypeId;
+use std::collections::HashSet;
+
+/// What `validate_dependencies` checks
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Checks {
+    /// Dependencies that aren't registered and cycles
+    pub(crate) graph: bool,
+    /// Singletons depending on scoped services, directly or through transient services
+    pub(crate) lifetimes: bool,
+}
+
+/// Services the container provides without a registration
+pub(crate) fn is_built_in(type_id: &TypeId) -> bool {
+    *type_id == TypeId::of::<ScopeId>()
+        || *type_id == TypeId::of::<CancellationToken>()
+        || *type_id == TypeId::of::<dyn Clock>()
+        || *type_id == TypeId::of::<WeakProvider>()
+}
+
+/// Depth first walk over the declared dependencies, collecting every dependency that isn't registered,
+/// closes a cycle or is scoped while a singleton depends on it, directly or through transient services
+struct Walker<'a> {
+    services: &'a TypeMap<ServiceInformation>,
+    checks: Checks,
+    path: Vec<TypeId>,
+    /// Services whose own dependencies were checked for missing registrations and cycles
+    walked: HashSet<TypeId>,
+    /// Services walked with the singleton that depends on them through transient services, if any
+    visited: HashSet<(TypeId, Option<TypeId>)>,
+    problems: Vec<Error>,
+}
+
+impl Walker<'_> {
+    /// `singleton` is the singleton that depends on `root` through transient services
+    fn walk(&mut self, root: &TypeId, singleton: Option<(TypeId, &'static str)>) {
+        if !self.visited.insert((*root, singleton.map(|(t, _)| t))) {
+            return;
+        }
+        let Some(information) = self.services.get(root) else {
+            return;
+        };
+        let first_walk = self.walked.insert(*root);
+        let singleton = match information.type_ {
+            ServiceType::Singleton => Some((*root, information.type_name)),
+            ServiceType::Scoped => None,
+            ServiceType::Transient => singleton,
+        };
+
+        self.path.push(*root);
+        for dependency in &information.dependencies {
+            if is_built_in(&dependency.type_id) {
+                continue;
+            }
+
+            let Some(dependency_information) = self.services.get(&dependency.type_id) else {
+                if self.checks.graph && first_walk {
+                    self.problems.push(Error::MissingDependency {
+                        service: information.type_name,
+                        dependency: dependency.type_name,
+                    });
+                }
+                continue;
+            };
+
+            if let Some((_, service)) = singleton.filter(|_| self.checks.lifetimes) {
+                if matches!(dependency_information.type_, ServiceType::Scoped) {
+                    self.problems.push(Error::WrongLifetimeContext {
+                        service,
+                        dependency: dependency.type_name,
+                    });
+                }
+            }
+
+            if let Some(start) = self.path.iter().position(|t| *t == dependency.type_id) {
+                if self.checks.graph && first_walk {
+                    let path = self.path[start..]
+                        .iter()
+                        .chain([&dependency.type_id])
+                        .filter_map(|t| Some(self.services.get(t)?.type_name))
+                        .collect();
+                    self.problems.push(Error::Cycle { path });
+                }
+                continue;
+            }
+
+            self.walk(&dependency.type_id, singleton);
+        }
+        self.path.pop();
+    }
+}
+
+/// Every problem `checks` finds in the declared dependencies of `roots` and the services they depend on,
+/// in the order they're walked. Each declared dependency and cycle is reported once,
+/// a scoped service once for every singleton depending on it
+pub(crate) fn find_problems<'a>(
+    services: &TypeMap<ServiceInformation>,
+    roots: impl IntoIterator<Item = &'a TypeId>,
+    checks: Checks,
+) -> Vec<Error> {
+    let mut walker = Walker {
+        services,
+        checks,
+        path: Vec::new(),
+        walked: HashSet::new(),
+        visited: HashSet::new(),
+        problems: Vec::new(),
+    };
+    for root in roots {
+        walker.walk(root, None);
+    }
+    walker.problems
+}
+
+/// Fails with the first problem `find_problems` reports
+pub(crate) fn validate_dependencies<'a>(
+    services: &TypeMap<ServiceInformation>,
+    roots: impl IntoIterator<Item = &'a TypeId>,
+    checks: Checks,
+) -> Result<(), Error> {
+    match find_problems(services, roots, checks).into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+impl ServiceCollection {
+    /// Creates a scope after verifying that the dependencies of every scoped service,
+    /// and their dependencies in turn, are registered, don't form a cycle
+    /// and don't make a singleton depend on a scoped service.
+    /// Services are checked in registration order, so the same problem is reported on every run.
+    /// Only dependencies declared through `Injectable::dependencies` can be verified
+    pub fn create_scope_validated(&self) -> Result<ServiceScope, Error> {
+        let services = self.service_info.snapshot();
+        let scoped = services
+            .iter()
+            .filter(|(_, information)| matches!(information.type_, ServiceType::Scoped))
+            .map(|(type_id, _)| type_id);
+        let checks = Checks {
+            graph: true,
+            lifetimes: true,
+        };
+        validate_dependencies(&services, scoped, checks)?;
+
+        Ok(ServiceScope::create(self))
+    }
+}
@@ -0,0 +1,114 @@
+use crate::trait_object::TraitObject;
+use crate::{Dep, ServiceCollection, ServiceHandler, ServiceInformation, ServiceType};
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+impl ServiceCollection {
+    /// Calls `f` with the type name and instance of every singleton that was initialized, in the order
+    /// they were initialized, e.g. to flush or dump the state of everything the container built.
+    /// Keyed and replaced registrations are included. Trait object registrations are visited as
+    /// their implementation, once, and left out if it isn't known, as with custom initializers.
+    /// Use `for_each_implementing` for those
+    pub fn visit_singletons(&self, mut f: impl FnMut(&'static str, &Arc<dyn Any + Send + Sync>)) {
+        let services = self.service_info.snapshot();
+        let mut singletons: Vec<_> = self
+            .singletons
+            .entries()
+            .into_iter()
+            .filter_map(|(created, type_id, value)| {
+                Some((created, type_id, services.get(&type_id)?, value))
+            })
+            .collect();
+        singletons.extend(self.keyed.singletons());
+        singletons.sort_by_key(|(created, _, _, _)| *created);
+
+        let mut visited: Vec<Arc<dyn Any + Send + Sync>> = Vec::new();
+        for (_, type_id, information, value) in singletons {
+            let (type_name, value) = if Any::type_id(value.as_ref()) == type_id {
+                (information.type_name, value)
+            } else {
+                let Some(implementation) = &information.implementation else {
+                    continue;
+                };
+                let Some(value) = implementation.extract(value.as_ref()) else {
+                    continue;
+                };
+                (implementation.dependency.type_name, value)
+            };
+
+            if visited.iter().any(|v| Arc::ptr_eq(v, &value)) {
+                continue;
+            }
+            f(type_name, &value);
+            visited.push(value);
+        }
+    }
+
+    /// Calls `f` once with every initialized singleton registered as the trait object `T`,
+    /// through `add_*_as`, `add_service_as` or `#[injectable(implements(...))]`, in registration order.
+    /// Replaced registrations are included, as are bindings of `implements(...)` whose service
+    /// was initialized without resolving the trait object
+    pub fn for_each_implementing<T: ?Sized + Send + Sync + 'static>(
+        &self,
+        mut f: impl FnMut(Dep<T>),
+    ) {
+        let type_id = TypeId::of::<T>();
+        let services = self.service_info.snapshot();
+        let registrations = self
+            .keyed
+            .of_type(&type_id)
+            .filter_map(|index| Some((Some(index), self.keyed.registration(index)?)))
+            .chain(
+                services
+                    .get(&type_id)
+                    .map(|information| (None, information)),
+            );
+
+        let mut visited: Vec<Arc<T>> = Vec::new();
+        for (index, information) in registrations {
+            let cached = match index {
+                Some(index) => self.keyed.singleton(index),
+                None => self.singletons.get(&type_id),
+            };
+            let value = match cached {
+                Some(value) => value,
+                // Only wraps the initialized service, so nothing new is built
+                None if self.shares_initialized(information) => {
+                    let resolved = match index {
+                        Some(index) => self.resolve_keyed_index(&type_id, index),
+                        None => self.try_get_service_by_type_id(&type_id),
+                    };
+                    let Ok(value) = resolved else {
+                        continue;
+                    };
+                    value
+                }
+                None => continue,
+            };
+
+            let Some(object) = value.downcast_ref::<TraitObject<T>>() else {
+                continue;
+            };
+            if visited.iter().any(|v| Arc::ptr_eq(v, &object.object)) {
+                continue;
+            }
+            visited.push(object.object.clone());
+            f(Dep(object.object.clone()));
+        }
+    }
+
+    /// Whether the singleton trait object `information` shares an implementation that was initialized
+    fn shares_initialized(&self, information: &ServiceInformation) -> bool {
+        matches!(information.type_, ServiceType::Singleton)
+            && information
+                .implementation
+                .as_ref()
+                .is_some_and(|implementation| {
+                    implementation.shared
+                        && self
+                            .singletons
+                            .get(&implementation.dependency.type_id)
+                            .is_some()
+                })
+    }
+}
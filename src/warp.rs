@@ -0,0 +1,54 @@
+//! Request scoped services for warp, e.g.
+//! `warp::path("users").and(scope(collection).inject::<Dep<UserService>>())`,
+//! or `scope(collection).handle(|scope| async move { ... })` to close the scope once the reply is ready
+
+use crate::{Error, FromScope, ServiceCollection, ServiceHandler, ServiceScope};
+use ::warp::{Filter, Rejection, Reply};
+use std::convert::Infallible;
+use std::future::Future;
+
+/// Rejection of a request whose services couldn't be resolved
+#[derive(Debug)]
+pub struct ResolveRejection(pub Error);
+
+impl ::warp::reject::Reject for ResolveRejection {}
+
+/// Creates a new scope of `collection` for every request.
+/// Filters can't run code after the filters chained to them, so the scope is dropped without being closed
+/// when the request is done. Use `InjectExt::handle` to close it with `ServiceScope::close` instead
+pub fn scope(
+    collection: ServiceCollection,
+) -> impl Filter<Extract = (ServiceScope,), Error = Infallible> + Clone {
+    ::warp::any().map(move || collection.create_scope())
+}
+
+/// Adds `inject` to the filter returned by `scope`
+pub trait InjectExt: Filter<Extract = (ServiceScope,), Error = Infallible> + Clone {
+    /// Resolves `T` from the request's scope, rejecting the request with `ResolveRejection` if it fails.
+    /// Resolve a tuple like `(Dep<A>, Dep<B>)` to use several services of the same scope
+    fn inject<T: FromScope + Send>(self) -> impl Filter<Extract = (T,), Error = Rejection> + Clone {
+        self.and_then(|scope: ServiceScope| async move {
+            T::from_scope(&scope).map_err(|e| ::warp::reject::custom(ResolveRejection(e)))
+        })
+    }
+
+    /// Replies with `handler`, which gets the request's scope,
+    /// and closes the scope with `ServiceScope::close` once the reply is ready, like the Rocket fairing does
+    fn handle<H, F, R>(self, handler: H) -> impl Filter<Extract = (R,), Error = Rejection> + Clone
+    where
+        H: Fn(ServiceScope) -> F + Clone + Send + Sync,
+        F: Future<Output = Result<R, Rejection>> + Send,
+        R: Reply,
+    {
+        self.and_then(move |scope: ServiceScope| {
+            let handler = handler.clone();
+            async move {
+                let reply = handler(scope.clone()).await;
+                scope.close().await;
+                reply
+            }
+        })
+    }
+}
+
+impl<F: Filter<Extract = (ServiceScope,), Error = Infallible> + Clone> InjectExt for F {}
@@ -0,0 +1,72 @@
+use crate::asynchronous::PendingServices;
+use crate::cache::ServiceCache;
+use crate::keyed::KeyedServices;
+use crate::memoize::MemoizedMap;
+use crate::observer::Observers;
+use crate::usage::Usage;
+use crate::{
+    CancellationToken, Registry, Resolver, RootScopePolicy, ServiceCollection, ServiceScope,
+};
+use std::any::Any;
+use std::sync::{Arc, OnceLock, Weak};
+
+/// Handle to the root collection that doesn't keep its singletons alive.
+/// Resolvable as `Dep<WeakProvider>` from the collection and every scope, so singletons can resolve services later
+/// without the reference cycle a `ServiceCollection` field would create, which leaks every singleton
+#[derive(Clone)]
+pub struct WeakProvider {
+    service_info: Registry,
+    singletons: Weak<ServiceCache>,
+    pending_singletons: PendingServices,
+    memoized: Weak<MemoizedMap>,
+    resolvers: Arc<Vec<Resolver>>,
+    observers: Observers,
+    usage: Arc<Usage>,
+    root_scope_policy: RootScopePolicy,
+    root_scope: Weak<OnceLock<ServiceScope>>,
+    cancellation_token: CancellationToken,
+    keyed: Weak<KeyedServices>,
+}
+
+impl WeakProvider {
+    /// The collection, or `None` once every handle to it has been dropped
+    pub fn upgrade(&self) -> Option<ServiceCollection> {
+        Some(ServiceCollection {
+            service_info: self.service_info.clone(),
+            singletons: self.singletons.upgrade()?,
+            pending_singletons: self.pending_singletons.clone(),
+            memoized: self.memoized.upgrade()?,
+            resolvers: self.resolvers.clone(),
+            observers: self.observers.clone(),
+            usage: self.usage.clone(),
+            root_scope_policy: self.root_scope_policy,
+            root_scope: self.root_scope.upgrade()?,
+            cancellation_token: self.cancellation_token.clone(),
+            keyed: self.keyed.upgrade()?,
+        })
+    }
+}
+
+impl ServiceCollection {
+    /// Handle to this collection that doesn't keep it alive, also resolvable as a `WeakProvider` service
+    pub fn downgrade(&self) -> WeakProvider {
+        WeakProvider {
+            service_info: self.service_info.clone(),
+            singletons: Arc::downgrade(&self.singletons),
+            pending_singletons: self.pending_singletons.clone(),
+            memoized: Arc::downgrade(&self.memoized),
+            resolvers: self.resolvers.clone(),
+            observers: self.observers.clone(),
+            usage: self.usage.clone(),
+            root_scope_policy: self.root_scope_policy,
+            root_scope: Arc::downgrade(&self.root_scope),
+            cancellation_token: self.cancellation_token.clone(),
+            keyed: Arc::downgrade(&self.keyed),
+        }
+    }
+}
+
+/// `WeakProvider` as a service
+pub(crate) fn service(collection: &ServiceCollection) -> Arc<dyn Any + Send + Sync> {
+    Arc::new(collection.downgrade())
+}
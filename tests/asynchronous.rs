@@ -0,0 +1,70 @@
+use deppy::{
+    AsyncInjectable, AsyncServiceHandler, Error, Memoize, ServiceCollectionBuilder, ServiceHandler,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+struct Token(usize);
+
+impl AsyncInjectable for Token {
+    async fn inject<T: AsyncServiceHandler>(_handler: &T) -> Result<Self, Error> {
+        Ok(Token(CREATED.fetch_add(1, Ordering::SeqCst)))
+    }
+}
+
+#[test]
+fn async_resolutions_run_through_middleware_and_memoize() {
+    let resolutions = Arc::new(AtomicUsize::new(0));
+    let counter = resolutions.clone();
+    let collection = ServiceCollectionBuilder::default()
+        .add_async_transient::<Token>()
+        .memoize(Memoize::Ttl(Duration::from_secs(60)))
+        .use_resolver(move |_, next| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            next.run()
+        })
+        .build();
+    let scope = collection.create_scope();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let first = collection.get_async_service::<Token>().await.unwrap();
+        let second = scope.get_async_service::<Token>().await.unwrap();
+        assert_eq!(first.0, second.0);
+    });
+
+    assert_eq!(CREATED.load(Ordering::SeqCst), 1);
+    assert_eq!(resolutions.load(Ordering::SeqCst), 2);
+}
+
+struct Connection;
+
+impl AsyncInjectable for Connection {
+    async fn inject<T: AsyncServiceHandler>(_handler: &T) -> Result<Self, Error> {
+        panic!("the middleware should have vetoed the initialization")
+    }
+}
+
+#[test]
+fn middleware_can_veto_async_resolutions_before_initializing() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_async_singleton::<Connection>()
+        .use_resolver(|context, next| match context.type_name {
+            Some(name) if name.ends_with("Connection") => Err(Error::Cancelled),
+            _ => next.run(),
+        })
+        .build();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    let result = runtime.block_on(collection.try_get_async_service::<Connection>());
+    assert!(matches!(result, Err(Error::Cancelled)));
+}
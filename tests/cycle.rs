@@ -0,0 +1,73 @@
+use deppy::{Dep, Error, ServiceCollectionBuilder, ServiceHandler, WeakProvider};
+use deppy_macros::Injectable;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Injectable)]
+struct A {
+    _b: Dep<B>,
+}
+
+#[derive(Injectable)]
+struct B {
+    _a: Dep<A>,
+}
+
+#[test]
+fn try_get_service_returns_a_cycle_of_derived_services() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_transient::<A>()
+        .add_transient::<B>()
+        .build();
+
+    let Err(Error::Cycle { path }) = collection.try_get_service::<A>() else {
+        panic!("expected a dependency cycle");
+    };
+    assert_eq!(path.len(), 3);
+    assert_eq!(path.first(), path.last());
+
+    let scope = collection.create_scope();
+    assert!(matches!(
+        scope.try_get_service::<B>(),
+        Err(Error::Cycle { .. })
+    ));
+}
+
+struct Outer;
+
+#[test]
+fn resolving_the_same_type_in_a_new_scope_isnt_a_cycle() {
+    static DEPTH: AtomicUsize = AtomicUsize::new(0);
+    let collection = ServiceCollectionBuilder::default()
+        .add_scoped_with(|provider| {
+            if DEPTH.fetch_add(1, Ordering::SeqCst) == 0 {
+                let collection = provider.get_required_service::<WeakProvider>().upgrade();
+                collection
+                    .unwrap()
+                    .create_scope()
+                    .try_get_service::<Outer>()?;
+            }
+            Ok::<_, Error>(Outer)
+        })
+        .build();
+
+    let scope = collection.create_scope();
+    let outer = scope.try_get_service::<Outer>();
+    assert!(outer.is_ok(), "{:?}", outer.err());
+    assert_eq!(DEPTH.load(Ordering::SeqCst), 2);
+}
+
+struct Left;
+struct Right;
+
+#[test]
+fn try_get_service_returns_a_cycle_of_factories() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_transient_with(|provider| provider.try_get_service::<Right>().map(|_| Left))
+        .add_transient_with(|provider| provider.try_get_service::<Left>().map(|_| Right))
+        .build();
+
+    assert!(matches!(
+        collection.try_get_service::<Left>(),
+        Err(Error::Cycle { .. })
+    ));
+}
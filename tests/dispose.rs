@@ -0,0 +1,118 @@
+use deppy::{
+    AsyncDispose, BoxFuture, CancellationToken, Error, ServiceCollectionBuilder, ServiceHandler,
+    ServiceProvider,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+static DISPOSED: AtomicBool = AtomicBool::new(false);
+
+struct Session;
+
+impl AsyncDispose for Session {
+    fn dispose(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async { DISPOSED.store(true, Ordering::SeqCst) })
+    }
+}
+
+#[test]
+fn run_scoped_async_closes_its_scope() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_scoped_with(|_| Ok::<_, Error>(Session))
+        .async_dispose::<Session>()
+        .build();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(collection.run_scoped_async(|scope| async move {
+        scope.get_required_service::<Session>();
+        assert!(!DISPOSED.load(Ordering::SeqCst));
+    }));
+
+    assert!(DISPOSED.load(Ordering::SeqCst));
+}
+
+static EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Records its name and whether its scope was cancelled when it's disposed
+struct Recorded {
+    name: &'static str,
+    token: CancellationToken,
+}
+
+impl Recorded {
+    fn new(provider: &ServiceProvider, name: &'static str) -> Self {
+        let token = provider.get_required_service::<CancellationToken>();
+        Self {
+            name,
+            token: CancellationToken::clone(&token),
+        }
+    }
+}
+
+impl AsyncDispose for Recorded {
+    fn dispose(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async {
+            let cancelled = self.token.is_cancelled();
+            EVENTS
+                .lock()
+                .unwrap()
+                .push(format!("{} {cancelled}", self.name));
+        })
+    }
+}
+
+struct Connection(Recorded);
+struct Repository(Recorded);
+
+impl AsyncDispose for Connection {
+    fn dispose(&self) -> BoxFuture<'_, ()> {
+        self.0.dispose()
+    }
+}
+
+impl AsyncDispose for Repository {
+    fn dispose(&self) -> BoxFuture<'_, ()> {
+        self.0.dispose()
+    }
+}
+
+#[test]
+fn close_cancels_the_scope_and_disposes_in_reverse_initialization_order() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_scoped_with(|provider| {
+            Ok::<_, Error>(Connection(Recorded::new(&provider, "connection")))
+        })
+        .async_dispose::<Connection>()
+        .add_scoped_with(|provider| {
+            provider.try_get_service::<Connection>()?;
+            Ok::<_, Error>(Repository(Recorded::new(&provider, "repository")))
+        })
+        .async_dispose::<Repository>()
+        // The first plugin is replaced, so it's cached with the keyed scoped services
+        .add_scoped_with(|provider| Ok::<_, Error>(Recorded::new(&provider, "first plugin")))
+        .async_dispose::<Recorded>()
+        .add_scoped_with(|provider| Ok::<_, Error>(Recorded::new(&provider, "second plugin")))
+        .async_dispose::<Recorded>()
+        .build();
+
+    let scope = collection.create_scope();
+    scope.get_required_service::<Repository>();
+    assert_eq!(scope.get_services::<Recorded>().len(), 2);
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(scope.close());
+
+    assert_eq!(
+        *EVENTS.lock().unwrap(),
+        [
+            "second plugin true",
+            "first plugin true",
+            "repository true",
+            "connection true"
+        ]
+    );
+}
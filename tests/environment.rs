@@ -0,0 +1,86 @@
+use deppy::{BuildOptions, Error, ServiceCollectionBuilder, ServiceHandler, ServiceProvider};
+use std::sync::Arc;
+
+trait Storage: Send + Sync {
+    fn name(&self) -> &'static str;
+}
+
+struct S3Storage;
+struct LocalStorage;
+
+impl Storage for S3Storage {
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+}
+
+impl Storage for LocalStorage {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+}
+
+fn builder() -> ServiceCollectionBuilder {
+    ServiceCollectionBuilder::default().add_singleton_per_env::<dyn Storage>(&[
+        ("prod", |_: ServiceProvider| -> Arc<dyn Storage> {
+            Arc::new(S3Storage)
+        }),
+        ("dev", |_: ServiceProvider| -> Arc<dyn Storage> {
+            Arc::new(LocalStorage)
+        }),
+    ])
+}
+
+#[test]
+fn every_build_method_picks_the_initializer_of_the_environment() {
+    let collection = builder().build_with_env("prod").unwrap();
+    assert_eq!(
+        collection.get_required_dyn_service::<dyn Storage>().name(),
+        "s3"
+    );
+
+    let collection = builder().env("dev").build();
+    assert_eq!(
+        collection.get_required_dyn_service::<dyn Storage>().name(),
+        "local"
+    );
+
+    let collection = builder()
+        .env("dev")
+        .build_with(BuildOptions::default())
+        .unwrap();
+    assert_eq!(
+        collection.get_required_dyn_service::<dyn Storage>().name(),
+        "local"
+    );
+
+    let collection = builder().env("prod").try_build().unwrap();
+    assert_eq!(
+        collection.get_required_dyn_service::<dyn Storage>().name(),
+        "s3"
+    );
+}
+
+#[test]
+fn an_unmatched_environment_is_an_error() {
+    let Err(Error::UnmatchedEnvironment { service, env }) = builder().build_with_env("staging")
+    else {
+        panic!("expected an unmatched environment");
+    };
+    assert!(service.contains("Storage"));
+    assert_eq!(env.as_deref(), Some("staging"));
+
+    let Err(problems) = builder().try_build() else {
+        panic!("expected an unmatched environment");
+    };
+    assert!(matches!(
+        problems.as_slice(),
+        [Error::UnmatchedEnvironment { env: None, .. }]
+    ));
+}
+
+#[test]
+#[should_panic(expected = "is registered per environment but no environment was set")]
+fn build_panics_without_an_environment() {
+    builder().build();
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "ffi")]
+
+use deppy::ffi::{
+    deppy_collection_free, deppy_resolve, deppy_scope_create, deppy_scope_free, deppy_service_free,
+    FfiCollection,
+};
+use deppy::ServiceCollectionBuilder;
+use std::convert::Infallible;
+
+struct Greeting(&'static str);
+struct Broken;
+
+#[test]
+fn resolves_services_by_stable_key_and_returns_null_on_panics() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_singleton_with(|_| Ok::<_, Infallible>(Greeting("hello")))
+        .stable_key("test.greeting")
+        .add_transient_with(|_| -> Result<Broken, Infallible> { panic!("initializer panicked") })
+        .stable_key("test.broken")
+        .build();
+    let collection = FfiCollection::new(collection).into_raw();
+
+    unsafe {
+        let scope = deppy_scope_create(collection);
+        assert!(!scope.is_null());
+
+        let greeting = deppy_resolve(scope, c"test.greeting".as_ptr());
+        assert_eq!((*greeting).get::<Greeting>().unwrap().0, "hello");
+        deppy_service_free(greeting);
+
+        assert!(deppy_resolve(scope, c"test.missing".as_ptr()).is_null());
+        assert!(deppy_resolve(scope, c"test.broken".as_ptr()).is_null());
+
+        deppy_scope_free(scope);
+        deppy_collection_free(collection);
+    }
+}
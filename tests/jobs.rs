@@ -0,0 +1,67 @@
+use deppy::{
+    AsyncDispose, BoxFuture, Dep, Error, JobHandler, JobQueue, RetryPolicy,
+    ServiceCollectionBuilder, ServiceHandler,
+};
+use deppy_macros::Injectable;
+use std::error::Error as StdError;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+static DISPOSED: AtomicUsize = AtomicUsize::new(0);
+
+struct Session;
+
+impl AsyncDispose for Session {
+    fn dispose(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async {
+            DISPOSED.fetch_add(1, Ordering::SeqCst);
+        })
+    }
+}
+
+struct SendMail;
+
+#[derive(Injectable)]
+struct SendMailHandler {
+    _session: Dep<Session>,
+}
+
+impl JobHandler<SendMail> for SendMailHandler {
+    fn handle<'a>(
+        &'a self,
+        _job: &'a SendMail,
+    ) -> BoxFuture<'a, Result<(), Box<dyn StdError + Send + Sync>>> {
+        Box::pin(async {
+            match ATTEMPTS.fetch_add(1, Ordering::SeqCst) {
+                0 => Err("the mail server is down".into()),
+                _ => Ok(()),
+            }
+        })
+    }
+}
+
+#[test]
+fn only_the_scope_of_a_successful_attempt_is_closed() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_job_queue(Some(RetryPolicy::fixed(3, Duration::ZERO)))
+        .add_scoped_with(|_| Ok::<_, Error>(Session))
+        .async_dispose::<Session>()
+        .add_job_handler::<SendMail, SendMailHandler>()
+        .build();
+    collection
+        .get_required_service::<JobQueue>()
+        .enqueue(SendMail);
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let jobs = tokio::time::timeout(Duration::from_millis(100), collection.run_jobs());
+        assert!(jobs.await.is_err(), "run_jobs only returns once cancelled");
+    });
+
+    assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 2);
+    assert_eq!(DISPOSED.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "mock")]
+
+use deppy::mock::MockDep;
+use deppy::{Dep, ServiceCollectionBuilder, ServiceHandler};
+use deppy_macros::Injectable;
+use mockall::automock;
+
+#[automock]
+trait Mailer: Send + Sync {
+    fn send(&self, to: &str) -> bool;
+}
+
+#[derive(Injectable)]
+struct Signup {
+    mailer: Dep<dyn Mailer>,
+}
+
+#[test]
+fn add_mock_resolves_the_mock_as_the_trait_object() {
+    let mailer = MockDep::new(MockMailer::new());
+    let collection = ServiceCollectionBuilder::default()
+        .add_mock::<dyn Mailer, _>(&mailer, |m| m)
+        .add_transient::<Signup>()
+        .build();
+
+    mailer.expect(|m| {
+        m.expect_send()
+            .withf(|to| to == "user@example.com")
+            .times(1)
+            .return_const(true);
+    });
+
+    let signup = collection.get_required_service::<Signup>();
+    assert!(signup.mailer.send("user@example.com"));
+    assert!(collection.get_implementation::<MockMailer>().is_some());
+    assert!(mailer.resolved().is_some());
+}
+
+#[test]
+#[should_panic(expected = "have to be set before it's resolved")]
+fn expectations_cant_be_set_after_resolution() {
+    let mailer = MockDep::new(MockMailer::new());
+    let collection = ServiceCollectionBuilder::default()
+        .add_mock::<dyn Mailer, _>(&mailer, |m| m)
+        .build();
+
+    collection.get_required_dyn_service::<dyn Mailer>();
+    mailer.expect(|m| {
+        m.expect_send().return_const(false);
+    });
+}
@@ -0,0 +1,157 @@
+use deppy::{
+    Dep, Error, Initialize, Injectable, RegistrationData, ServiceCollectionBuilder, ServiceHandler,
+    ServiceType,
+};
+use deppy_macros::Injectable;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+struct Plugin(u32);
+
+#[test]
+fn get_services_resolves_replaced_registrations_through_middleware() {
+    let resolutions = Arc::new(AtomicUsize::new(0));
+    let counter = resolutions.clone();
+    let collection = ServiceCollectionBuilder::default()
+        .add_singleton_with(|_| Ok::<_, Error>(Plugin(1)))
+        .add_transient_with(|_| Err::<Plugin, _>("broken"))
+        .add_singleton_with(|_| Ok::<_, Error>(Plugin(3)))
+        .use_resolver(move |_, next| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            next.run()
+        })
+        .build();
+
+    let plugins = collection.get_services::<Plugin>();
+    assert_eq!(plugins.iter().map(|p| p.0).collect::<Vec<_>>(), [1, 3]);
+    assert_eq!(resolutions.load(Ordering::SeqCst), 3);
+    assert!(collection.try_get_services::<Plugin>().is_err());
+
+    let scope = collection.create_scope();
+    assert_eq!(scope.get_services::<Plugin>().len(), 2);
+}
+
+#[test]
+fn reload_discards_replaced_singletons() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_singleton_with(|_| Ok::<_, Error>(Plugin(1)))
+        .add_singleton_with(|_| Ok::<_, Error>(Plugin(2)))
+        .build();
+
+    let first = collection.get_services::<Plugin>();
+    assert!(std::ptr::eq(
+        &*first[0],
+        &*collection.get_services::<Plugin>()[0]
+    ));
+    assert!(collection.reload::<Plugin>());
+    assert!(!std::ptr::eq(
+        &*first[0],
+        &*collection.get_services::<Plugin>()[0]
+    ));
+}
+
+#[test]
+fn get_services_returns_replaced_instances() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_instance(Plugin(1))
+        .add_instance(Plugin(2))
+        .build();
+
+    let plugins = collection.get_services::<Plugin>();
+    assert_eq!(plugins.iter().map(|p| p.0).collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(collection.get_service::<Plugin>().unwrap().0, 2);
+}
+
+struct Missing;
+struct Report;
+
+#[test]
+#[should_panic(
+    expected = "Service services::Report couldn't be resolved: Service services::Missing couldn't be found (requested by services::Report)"
+)]
+fn get_required_service_blames_the_missing_dependency() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_transient_with(|provider| provider.try_get_service::<Missing>().map(|_| Report))
+        .build();
+
+    collection.get_required_service::<Report>();
+}
+
+#[derive(Clone)]
+struct GreetingInitializer;
+
+struct Greeting(String);
+
+impl Initialize<Greeting> for GreetingInitializer {
+    fn initialize<T: ServiceHandler>(&self, _handler: &T) -> Greeting {
+        Greeting("hello".to_owned())
+    }
+
+    fn initialize_with_data<T: ServiceHandler>(
+        &self,
+        handler: &T,
+        data: &RegistrationData,
+    ) -> Greeting {
+        match data.get::<&'static str>() {
+            Some(name) => Greeting(format!("hello {name}")),
+            None => self.initialize(handler),
+        }
+    }
+}
+
+#[test]
+fn with_data_is_handed_to_the_initializer() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_service(ServiceType::Singleton, GreetingInitializer)
+        .with_data("world")
+        .build();
+
+    assert_eq!(
+        collection.get_required_service::<Greeting>().0,
+        "hello world"
+    );
+}
+
+#[test]
+#[should_panic(expected = "can't take data")]
+fn with_data_is_rejected_for_factories() {
+    let _ = ServiceCollectionBuilder::default()
+        .add_singleton_with(|_| Ok::<_, Error>(Greeting("hello".to_owned())))
+        .with_data("world");
+}
+
+trait Greeter: Send + Sync {
+    fn greet(&self) -> String;
+}
+
+#[derive(Injectable)]
+struct English;
+
+impl Greeter for English {
+    fn greet(&self) -> String {
+        "hello".to_owned()
+    }
+}
+
+#[derive(Injectable)]
+struct Welcome {
+    #[injectable(with = |greeter: Dep<dyn Greeter>| greeter.greet())]
+    message: String,
+}
+
+#[test]
+fn with_closures_take_trait_objects_and_declare_them() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_singleton_as::<dyn Greeter, English>(|c| c)
+        .add_singleton::<Welcome>()
+        .build();
+
+    assert_eq!(
+        collection.get_required_service::<Welcome>().message,
+        "hello"
+    );
+    assert_eq!(
+        Welcome::dependencies()[0].type_name,
+        std::any::type_name::<dyn Greeter>()
+    );
+}
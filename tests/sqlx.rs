@@ -0,0 +1,69 @@
+#![cfg(feature = "sqlx")]
+
+use deppy::sqlx::DbTransaction;
+use deppy::{AsyncServiceHandler, ServiceCollection, ServiceCollectionBuilder, ServiceHandler};
+use sqlx::pool::PoolOptions;
+use sqlx::{Pool, Sqlite};
+
+async fn collection() -> ServiceCollection {
+    // A single connection, so every scope sees the same in-memory database
+    let options = PoolOptions::<Sqlite>::new().max_connections(1);
+    let collection = ServiceCollectionBuilder::default()
+        .add_pool::<Sqlite>(options, "sqlite::memory:")
+        .add_transaction::<Sqlite>()
+        .build();
+    let pool = collection
+        .get_async_service::<Pool<Sqlite>>()
+        .await
+        .unwrap();
+    sqlx::query("CREATE TABLE users (name TEXT)")
+        .execute(&*pool)
+        .await
+        .unwrap();
+    collection
+}
+
+async fn insert_user(collection: &ServiceCollection, commit: bool) {
+    let scope = collection.create_scope();
+    let transaction = scope
+        .get_async_service::<DbTransaction<Sqlite>>()
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO users VALUES ('ada')")
+        .execute(&mut **transaction.get().await.unwrap())
+        .await
+        .unwrap();
+    if commit {
+        transaction.commit().await.unwrap();
+    }
+    drop(transaction);
+    scope.close().await;
+}
+
+async fn count_users(collection: &ServiceCollection) -> i64 {
+    let pool = collection
+        .get_async_service::<Pool<Sqlite>>()
+        .await
+        .unwrap();
+    sqlx::query_scalar("SELECT COUNT(*) FROM users")
+        .fetch_one(&*pool)
+        .await
+        .unwrap()
+}
+
+#[test]
+fn closing_a_scope_rolls_back_and_commit_persists() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let collection = collection().await;
+
+        insert_user(&collection, false).await;
+        assert_eq!(count_users(&collection).await, 0);
+
+        insert_user(&collection, true).await;
+        assert_eq!(count_users(&collection).await, 1);
+    });
+}
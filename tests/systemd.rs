@@ -0,0 +1,77 @@
+#![cfg(all(feature = "systemd", unix))]
+
+use deppy::systemd::HealthCheck;
+use deppy::{CancellationToken, ServiceCollectionBuilder};
+use deppy_macros::Injectable;
+use std::os::unix::net::UnixDatagram;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+static HEALTHY: AtomicBool = AtomicBool::new(true);
+
+#[derive(Injectable)]
+struct Database;
+
+impl HealthCheck for Database {
+    fn is_healthy(&self) -> bool {
+        HEALTHY.load(Ordering::SeqCst)
+    }
+}
+
+fn received(socket: &UnixDatagram) -> Vec<String> {
+    let mut messages = Vec::new();
+    let mut buffer = [0; 64];
+    while let Ok(length) = socket.recv(&mut buffer) {
+        messages.push(String::from_utf8_lossy(&buffer[..length]).into_owned());
+    }
+    messages
+}
+
+#[test]
+fn notifies_readiness_and_pings_the_watchdog_while_healthy() {
+    let path = std::env::temp_dir().join(format!("deppy-notify-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let socket = UnixDatagram::bind(&path).unwrap();
+    socket.set_nonblocking(true).unwrap();
+    std::env::set_var("NOTIFY_SOCKET", &path);
+    std::env::set_var("WATCHDOG_USEC", "20000");
+    std::env::remove_var("WATCHDOG_PID");
+
+    let token = CancellationToken::new();
+    let collection = ServiceCollectionBuilder::default()
+        .add_singleton_as::<dyn HealthCheck, Database>(|c| c)
+        .cancellation_token(token.clone())
+        .build();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    let watch = |duration| {
+        runtime.block_on(async {
+            let _ = tokio::time::timeout(duration, collection.run_watchdog()).await;
+        })
+    };
+
+    runtime
+        .block_on(collection.initialize_and_notify_ready())
+        .unwrap();
+    assert_eq!(received(&socket), ["READY=1"]);
+
+    watch(Duration::from_millis(50));
+    let pings = received(&socket);
+    assert!(!pings.is_empty());
+    assert!(pings.iter().all(|ping| ping == "WATCHDOG=1"));
+
+    HEALTHY.store(false, Ordering::SeqCst);
+    watch(Duration::from_millis(50));
+    assert!(received(&socket).is_empty());
+
+    token.cancel();
+    runtime.block_on(async {
+        tokio::time::timeout(Duration::from_secs(1), collection.run_watchdog())
+            .await
+            .expect("the watchdog stops with the container");
+    });
+
+    let _ = std::fs::remove_file(&path);
+}
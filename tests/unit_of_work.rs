@@ -0,0 +1,83 @@
+use deppy::{
+    AsyncDispose, BoxFuture, Error, Participant, ServiceCollection, ServiceCollectionBuilder,
+    ServiceHandler, UnitOfWork,
+};
+use std::error::Error as StdError;
+use std::sync::{Arc, Mutex};
+
+type Events = Arc<Mutex<Vec<&'static str>>>;
+
+struct Outbox(Events);
+
+impl Participant for Outbox {
+    fn commit(&self) -> BoxFuture<'_, Result<(), Box<dyn StdError + Send + Sync>>> {
+        Box::pin(async {
+            self.0.lock().unwrap().push("commit");
+            Ok(())
+        })
+    }
+
+    fn rollback(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async { self.0.lock().unwrap().push("rollback") })
+    }
+}
+
+impl AsyncDispose for Outbox {
+    fn dispose(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async { self.0.lock().unwrap().push("dispose") })
+    }
+}
+
+fn collection(events: &Events) -> ServiceCollection {
+    let events = events.clone();
+    ServiceCollectionBuilder::default()
+        .add_unit_of_work()
+        .add_scoped_with(move |_| Ok::<_, Error>(Outbox(events.clone())))
+        .async_dispose::<Outbox>()
+        .build()
+}
+
+fn run(collection: &ServiceCollection, succeed: bool) -> Result<(), Error> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(collection.run_unit_of_work(|scope| async move {
+        let unit_of_work = scope.try_get_service::<UnitOfWork>()?;
+        unit_of_work.enlist(scope.try_get_service::<Outbox>()?);
+        match succeed {
+            true => Ok(()),
+            false => Err(Error::InitializationFailed {
+                source: "the work failed".into(),
+            }),
+        }
+    }))
+}
+
+#[test]
+fn successful_work_is_committed_and_the_scope_closed() {
+    let events = Events::default();
+    assert!(run(&collection(&events), true).is_ok());
+    assert_eq!(*events.lock().unwrap(), ["commit", "dispose"]);
+}
+
+#[test]
+fn failed_work_is_rolled_back_without_closing_the_scope() {
+    let events = Events::default();
+    assert!(run(&collection(&events), false).is_err());
+    assert_eq!(*events.lock().unwrap(), ["rollback"]);
+}
+
+#[test]
+fn closing_a_scope_rolls_back_uncommitted_work() {
+    let events = Events::default();
+    let scope = collection(&events).create_scope();
+    let unit_of_work = scope.get_required_service::<UnitOfWork>();
+    unit_of_work.enlist(scope.get_required_service::<Outbox>());
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(scope.close());
+    assert!(events.lock().unwrap().contains(&"rollback"));
+    assert!(!events.lock().unwrap().contains(&"commit"));
+}
@@ -0,0 +1,56 @@
+use deppy::{BuildOptions, Dep, Error, ServiceCollectionBuilder};
+use deppy_macros::Injectable;
+
+#[derive(Injectable)]
+struct Request;
+
+#[derive(Injectable)]
+struct Handler {
+    _request: Dep<Request>,
+}
+
+#[derive(Injectable)]
+struct Cache {
+    _handler: Dep<Handler>,
+}
+
+fn builder() -> ServiceCollectionBuilder {
+    ServiceCollectionBuilder::default()
+        .add_scoped::<Request>()
+        .add_transient::<Handler>()
+        .add_singleton::<Cache>()
+}
+
+#[test]
+fn build_with_finds_scoped_services_captured_through_transients() {
+    let options = BuildOptions {
+        validate_scopes: true,
+        ..Default::default()
+    };
+    let Err(Error::WrongLifetimeContext {
+        service,
+        dependency,
+    }) = builder().build_with(options)
+    else {
+        panic!("expected a captured scoped service");
+    };
+    assert_eq!(service, std::any::type_name::<Cache>());
+    assert_eq!(dependency, std::any::type_name::<Request>());
+}
+
+#[test]
+fn try_build_reports_every_problem() {
+    #[derive(Injectable)]
+    struct Missing;
+    #[derive(Injectable)]
+    struct Orphan {
+        _missing: Dep<Missing>,
+    }
+
+    let Err(problems) = builder().add_transient::<Orphan>().try_build() else {
+        panic!("expected problems");
+    };
+    assert_eq!(problems.len(), 2);
+    assert!(matches!(problems[0], Error::WrongLifetimeContext { .. }));
+    assert!(matches!(problems[1], Error::MissingDependency { .. }));
+}
@@ -0,0 +1,61 @@
+use deppy::{Error, ServiceCollectionBuilder, ServiceHandler};
+use deppy_macros::Injectable;
+
+trait Plugin: Send + Sync {
+    fn name(&self) -> &'static str;
+}
+
+#[derive(Injectable)]
+#[injectable(implements(dyn Plugin))]
+struct Metrics;
+
+#[derive(Injectable)]
+#[injectable(implements(dyn Plugin))]
+struct Audit;
+
+impl Plugin for Metrics {
+    fn name(&self) -> &'static str {
+        "metrics"
+    }
+}
+
+impl Plugin for Audit {
+    fn name(&self) -> &'static str {
+        "audit"
+    }
+}
+
+struct Pool(&'static str);
+
+#[test]
+fn for_each_implementing_visits_every_binding_of_initialized_singletons() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_singleton::<Metrics>()
+        .add_singleton::<Audit>()
+        .build();
+    collection.get_required_service::<Metrics>();
+    collection.get_required_service::<Audit>();
+
+    let mut names = Vec::new();
+    collection.for_each_implementing::<dyn Plugin>(|plugin| names.push(plugin.name()));
+    assert_eq!(names, ["metrics", "audit"]);
+}
+
+#[test]
+fn visit_singletons_includes_keyed_singletons() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_keyed_singleton("primary", |_| Ok::<_, Error>(Pool("primary")))
+        .add_singleton::<Metrics>()
+        .build();
+    collection.get_required_service::<Metrics>();
+    collection.get_keyed_service::<Pool>("primary").unwrap();
+
+    let mut names = Vec::new();
+    collection.visit_singletons(|type_name, value| {
+        names.push(type_name);
+        if let Some(pool) = value.downcast_ref::<Pool>() {
+            assert_eq!(pool.0, "primary");
+        }
+    });
+    assert_eq!(names, ["visit::Metrics", "visit::Pool"]);
+}
@@ -0,0 +1,35 @@
+#![cfg(feature = "warp")]
+
+use deppy::warp::{scope, InjectExt};
+use deppy::{AsyncDispose, BoxFuture, Error, ServiceCollectionBuilder, ServiceHandler};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static DISPOSED: AtomicBool = AtomicBool::new(false);
+
+struct Session;
+
+impl AsyncDispose for Session {
+    fn dispose(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async { DISPOSED.store(true, Ordering::SeqCst) })
+    }
+}
+
+#[test]
+fn handle_closes_the_scope_after_the_reply() {
+    let collection = ServiceCollectionBuilder::default()
+        .add_scoped_with(|_| Ok::<_, Error>(Session))
+        .async_dispose::<Session>()
+        .build();
+    let route = scope(collection).handle(|scope| async move {
+        scope.get_required_service::<Session>();
+        Ok(::warp::reply())
+    });
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let response = runtime.block_on(::warp::test::request().reply(&route));
+
+    assert_eq!(response.status(), 200);
+    assert!(DISPOSED.load(Ordering::SeqCst));
+}